@@ -96,27 +96,224 @@ pub fn py_annindex(attr: TokenStream, item: TokenStream) -> TokenStream {
             }
 
             fn save(&self, path: String) -> PyResult<()> {
-                Self::validate_path(&path)?;
-                self.inner.save(&path);
-                Ok(())
+                let validated = crate::path_validation::ValidatedPath::try_from(path.as_str())?;
+                self.save_to(&validated)
             }
-            
+
             #[staticmethod]
             fn load(path: String) -> pyo3::PyResult<Self> {
-                if path.contains("..") || path.starts_with('/') || path.starts_with("\\") {
-                    return Err(pyo3::exceptions::PyValueError::new_err("Invalid file path"));
+                let validated = crate::path_validation::ValidatedPath::try_from(path.as_str())?;
+                Self::load_from(&validated)
+            }
+
+            /// Like `save`, but takes raw path bytes (e.g. Python
+            /// `os.fsencode(path)`) instead of a `str`, so destinations that
+            /// aren't valid UTF-8 — legal and not uncommon on Linux — can be
+            /// saved to without a lossy re-encoding.
+            #[cfg(unix)]
+            fn save_bytes(&self, path: Vec<u8>) -> PyResult<()> {
+                let validated = crate::path_validation::ValidatedPath::try_from(path.as_slice())?;
+                self.save_to(&validated)
+            }
+
+            /// Like `load`, but takes raw path bytes (see `save_bytes`).
+            #[cfg(unix)]
+            #[staticmethod]
+            fn load_bytes(path: Vec<u8>) -> pyo3::PyResult<Self> {
+                let validated = crate::path_validation::ValidatedPath::try_from(path.as_slice())?;
+                Self::load_from(&validated)
+            }
+
+            /// Pack this index into a self-describing `.tar` bundle: a
+            /// `manifest.json` (dim, distance metric, format version, and a
+            /// CRC32 over the payload) alongside a `payload.bin` entry
+            /// holding the backend's native serialization. Safer to inspect,
+            /// version, and migrate than the opaque blob `save` writes.
+            fn save_bundle(&self, path: String) -> pyo3::PyResult<()> {
+                let dest = crate::path_validation::ValidatedPath::try_from(path.as_str())?;
+
+                let staging_dir = std::env::temp_dir().join(format!(
+                    "rust_annie_bundle_save_{}_{:?}",
+                    std::process::id(),
+                    std::thread::current().id(),
+                ));
+                std::fs::create_dir_all(&staging_dir)
+                    .map_err(|e| pyo3::exceptions::PyIOError::new_err(format!("Failed to create staging dir: {}", e)))?;
+                let payload_path = staging_dir.join("payload.bin");
+                self.inner.save(&payload_path.to_string_lossy());
+                let payload = std::fs::read(&payload_path)
+                    .map_err(|e| pyo3::exceptions::PyIOError::new_err(format!("Failed to read staged payload: {}", e)));
+                let _ = std::fs::remove_dir_all(&staging_dir);
+                let payload = payload?;
+
+                let crc = Self::bundle_crc32(&payload);
+                let manifest = format!(
+                    "{{\"dim\":{},\"metric\":\"{:?}\",\"format_version\":1,\"crc32\":{}}}",
+                    self.inner.dims(),
+                    #distance_metric,
+                    crc,
+                );
+
+                let file = std::fs::File::create(dest.as_path())
+                    .map_err(|e| pyo3::exceptions::PyIOError::new_err(format!("Failed to create bundle: {}", e)))?;
+                let mut builder = tar::Builder::new(file);
+
+                let mut manifest_header = tar::Header::new_gnu();
+                manifest_header.set_size(manifest.len() as u64);
+                manifest_header.set_mode(0o644);
+                manifest_header.set_cksum();
+                builder
+                    .append_data(&mut manifest_header, "manifest.json", manifest.as_bytes())
+                    .map_err(|e| pyo3::exceptions::PyIOError::new_err(format!("Failed to write manifest: {}", e)))?;
+
+                let mut payload_header = tar::Header::new_gnu();
+                payload_header.set_size(payload.len() as u64);
+                payload_header.set_mode(0o644);
+                payload_header.set_cksum();
+                builder
+                    .append_data(&mut payload_header, "payload.bin", payload.as_slice())
+                    .map_err(|e| pyo3::exceptions::PyIOError::new_err(format!("Failed to write payload: {}", e)))?;
+
+                builder
+                    .finish()
+                    .map_err(|e| pyo3::exceptions::PyIOError::new_err(format!("Failed to finalize bundle: {}", e)))
+            }
+
+            /// Load an index previously written by `save_bundle`, hardening
+            /// extraction against malicious archives: every entry path is
+            /// validated component-by-component (no `..`, no root, no drive
+            /// prefix), links are rejected, total uncompressed size is
+            /// capped, and the payload's CRC32 is checked against the
+            /// manifest before the backend ever sees it.
+            #[staticmethod]
+            fn load_bundle(path: String) -> pyo3::PyResult<Self> {
+                const MAX_BUNDLE_BYTES: u64 = 1024 * 1024 * 1024; // 1 GiB
+
+                let src = crate::path_validation::ValidatedPath::try_from(path.as_str())?;
+                let file = std::fs::File::open(src.as_path())
+                    .map_err(|e| pyo3::exceptions::PyIOError::new_err(format!("Failed to open bundle: {}", e)))?;
+                let mut archive = tar::Archive::new(file);
+
+                let mut manifest_bytes: Option<Vec<u8>> = None;
+                let mut payload_bytes: Option<Vec<u8>> = None;
+                let mut total_bytes: u64 = 0;
+
+                let entries = archive
+                    .entries()
+                    .map_err(|e| pyo3::exceptions::PyIOError::new_err(format!("Corrupt bundle: {}", e)))?;
+                for entry in entries {
+                    let mut entry = entry
+                        .map_err(|e| pyo3::exceptions::PyIOError::new_err(format!("Corrupt bundle entry: {}", e)))?;
+                    let entry_path = entry
+                        .path()
+                        .map_err(|e| pyo3::exceptions::PyIOError::new_err(format!("Invalid entry path: {}", e)))?
+                        .into_owned();
+
+                    for component in entry_path.components() {
+                        match component {
+                            std::path::Component::Normal(_) | std::path::Component::CurDir => {}
+                            std::path::Component::ParentDir
+                            | std::path::Component::RootDir
+                            | std::path::Component::Prefix(_) => {
+                                return Err(pyo3::exceptions::PyValueError::new_err(
+                                    "Bundle entry path escapes the extraction directory",
+                                ));
+                            }
+                        }
+                    }
+
+                    let entry_type = entry.header().entry_type();
+                    if entry_type.is_symlink() || entry_type.is_hard_link() {
+                        return Err(pyo3::exceptions::PyValueError::new_err("Bundle entries may not be links"));
+                    }
+
+                    let size = entry.header().size().unwrap_or(0);
+                    total_bytes = total_bytes.saturating_add(size);
+                    if total_bytes > MAX_BUNDLE_BYTES {
+                        return Err(pyo3::exceptions::PyValueError::new_err(
+                            "Bundle exceeds maximum allowed uncompressed size",
+                        ));
+                    }
+
+                    let mut buf = Vec::with_capacity(size as usize);
+                    std::io::Read::read_to_end(&mut entry, &mut buf)
+                        .map_err(|e| pyo3::exceptions::PyIOError::new_err(format!("Failed to read bundle entry: {}", e)))?;
+
+                    match entry_path.to_string_lossy().as_ref() {
+                        "manifest.json" => manifest_bytes = Some(buf),
+                        "payload.bin" => payload_bytes = Some(buf),
+                        _ => {} // Forward-compatible: ignore unrecognized/backend-specific entries
+                    }
+                }
+
+                let manifest_bytes = manifest_bytes
+                    .ok_or_else(|| pyo3::exceptions::PyValueError::new_err("Bundle is missing manifest.json"))?;
+                let payload = payload_bytes
+                    .ok_or_else(|| pyo3::exceptions::PyValueError::new_err("Bundle is missing payload.bin"))?;
+
+                let manifest = String::from_utf8(manifest_bytes)
+                    .map_err(|_| pyo3::exceptions::PyValueError::new_err("manifest.json is not valid UTF-8"))?;
+                let expected_crc: u32 = manifest
+                    .rsplit("\"crc32\":")
+                    .next()
+                    .map(|tail| tail.trim_start_matches(|c: char| !c.is_ascii_digit()))
+                    .and_then(|tail| tail.split(|c: char| !c.is_ascii_digit()).next())
+                    .and_then(|digits| digits.parse().ok())
+                    .ok_or_else(|| pyo3::exceptions::PyValueError::new_err("manifest.json is missing crc32"))?;
+
+                if Self::bundle_crc32(&payload) != expected_crc {
+                    return Err(pyo3::exceptions::PyValueError::new_err("Bundle payload failed CRC32 verification"));
                 }
-                match #name::load(&path) {
+
+                let staging_dir = std::env::temp_dir().join(format!(
+                    "rust_annie_bundle_load_{}_{:?}",
+                    std::process::id(),
+                    std::thread::current().id(),
+                ));
+                std::fs::create_dir_all(&staging_dir)
+                    .map_err(|e| pyo3::exceptions::PyIOError::new_err(format!("Failed to create staging dir: {}", e)))?;
+                let payload_path = staging_dir.join("payload.bin");
+                std::fs::write(&payload_path, &payload)
+                    .map_err(|e| pyo3::exceptions::PyIOError::new_err(format!("Failed to stage payload: {}", e)))?;
+
+                let result = #name::load(&payload_path.to_string_lossy())
+                    .map(|inner| #py_name { inner })
+                    .map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()));
+                let _ = std::fs::remove_dir_all(&staging_dir);
+                result
+            }
+        }
+
+        impl #py_name {
+            /// Write this index's native serialization to `validated`. Takes
+            /// `&ValidatedPath` rather than a bare `&str` so the type system
+            /// guarantees the destination has already passed the path
+            /// auditor.
+            fn save_to(&self, validated: &crate::path_validation::ValidatedPath) -> pyo3::PyResult<()> {
+                self.inner.save(&validated.to_string_lossy());
+                Ok(())
+            }
+
+            /// Load an index from `validated`'s native serialization.
+            fn load_from(validated: &crate::path_validation::ValidatedPath) -> pyo3::PyResult<Self> {
+                match #name::load(&validated.to_string_lossy()) {
                     Ok(inner) => Ok(#py_name { inner }),
                     Err(e) => Err(pyo3::exceptions::PyIOError::new_err(e.to_string())),
                 }
             }
 
-            fn validate_path(path: &str) -> PyResult<()> {
-                if path.contains("..") || path.starts_with('/') || path.starts_with("\\") {
-                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Invalid file path"));
+            /// IEEE CRC-32 (the polynomial used by zip/gzip/tar) of `data`,
+            /// used to detect a corrupted or tampered bundle payload.
+            fn bundle_crc32(data: &[u8]) -> u32 {
+                let mut crc: u32 = 0xFFFF_FFFF;
+                for &byte in data {
+                    crc ^= byte as u32;
+                    for _ in 0..8 {
+                        let mask = (crc & 1).wrapping_neg();
+                        crc = (crc >> 1) ^ (0xEDB88320 & mask);
+                    }
                 }
-                Ok(())
+                !crc
             }
         }
     };
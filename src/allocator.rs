@@ -0,0 +1,60 @@
+//! Opt-in jemalloc global allocator for large, frequently-mutated indices.
+//!
+//! The system allocator tends to hold onto freed `entries`/vector buffers
+//! instead of returning them to the OS, so a long-lived `AnnIndex` that's
+//! seen millions of pushes, tombstones, and `compact()` calls can sit on a
+//! much larger RSS than its live data. Enabling the `jemalloc` feature
+//! installs jemalloc as the global allocator and lets callers tune its
+//! dirty/muzzy page decay so freed pages are returned to the OS promptly
+//! after a `compact()` or a large `remove` batch.
+
+#[cfg(feature = "jemalloc")]
+#[global_allocator]
+static GLOBAL: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+#[cfg(feature = "jemalloc")]
+use crate::errors::RustAnnError;
+
+/// jemalloc's "all arenas" pseudo-index, used to apply a decay setting to
+/// every arena rather than just the calling thread's.
+#[cfg(feature = "jemalloc")]
+const MALLCTL_ARENAS_ALL: u32 = 4096;
+
+/// Set jemalloc's dirty and muzzy page decay (in milliseconds) across all
+/// arenas. A small `dirty_decay_ms` (and `muzzy_decay_ms` of `0` to disable
+/// muzzy decay entirely) makes RSS drop quickly after a `compact()` or a
+/// large `remove` batch, at the cost of more `madvise` syscalls under
+/// churn.
+#[cfg(feature = "jemalloc")]
+pub fn set_decay_ms(dirty_decay_ms: i64, muzzy_decay_ms: i64) -> Result<(), RustAnnError> {
+    let dirty_key = format!("arena.{}.dirty_decay_ms\0", MALLCTL_ARENAS_ALL);
+    let muzzy_key = format!("arena.{}.muzzy_decay_ms\0", MALLCTL_ARENAS_ALL);
+    unsafe {
+        tikv_jemalloc_ctl::raw::write(dirty_key.as_bytes(), dirty_decay_ms as isize)
+            .map_err(|e| RustAnnError::Allocation(format!("failed to set dirty_decay_ms: {e}")))?;
+        tikv_jemalloc_ctl::raw::write(muzzy_key.as_bytes(), muzzy_decay_ms as isize)
+            .map_err(|e| RustAnnError::Allocation(format!("failed to set muzzy_decay_ms: {e}")))?;
+    }
+    Ok(())
+}
+
+/// Current resident and allocated byte counts, refreshed from jemalloc's
+/// stats epoch. Surfaced through `AnnIndex::get_metrics` so memory
+/// behavior can be watched alongside index size.
+#[cfg(feature = "jemalloc")]
+pub fn stats() -> Result<AllocatorStats, RustAnnError> {
+    tikv_jemalloc_ctl::epoch::advance()
+        .map_err(|e| RustAnnError::Allocation(format!("failed to refresh jemalloc stats epoch: {e}")))?;
+    let resident = tikv_jemalloc_ctl::stats::resident::read()
+        .map_err(|e| RustAnnError::Allocation(format!("failed to read resident stat: {e}")))?;
+    let allocated = tikv_jemalloc_ctl::stats::allocated::read()
+        .map_err(|e| RustAnnError::Allocation(format!("failed to read allocated stat: {e}")))?;
+    Ok(AllocatorStats { resident_bytes: resident as u64, allocated_bytes: allocated as u64 })
+}
+
+#[cfg(feature = "jemalloc")]
+#[derive(Debug, Clone, Copy)]
+pub struct AllocatorStats {
+    pub resident_bytes: u64,
+    pub allocated_bytes: u64,
+}
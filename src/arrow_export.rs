@@ -0,0 +1,141 @@
+//! Zero-copy-where-possible Arrow export of [`AnnIndex`]'s columnar
+//! storage, so dataframe engines (Polars, pandas-on-pyarrow) can run
+//! relational filtering over ids/vectors/metadata directly, then push the
+//! surviving rows back into a k-NN scan via `search_masked` /
+//! `search_batch_masked`.
+//!
+//! Requires the `arrow` feature.
+
+use std::sync::Arc;
+
+use arrow::array::{
+    ArrayRef, Float32Array, Float64Array, Int64Array, ListBuilder, StringArray, StringBuilder,
+};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+
+use crate::errors::RustAnnError;
+use crate::index::{AnnIndex, MetadataType, MetadataValue};
+
+/// Build a `RecordBatch` with one row per live (non-tombstoned) entry:
+/// `id` (Int64), `vector` (fixed-size list of Float32), and one column per
+/// field in `index.metadata_schema`. The `id` and `vector` columns wrap
+/// their source `Vec`s directly via Arrow's `Buffer::from_vec`, so they
+/// don't get reallocated; metadata columns are rebuilt per-field since
+/// `MetadataValue` isn't itself a columnar representation.
+pub fn index_to_record_batch(index: &AnnIndex) -> Result<RecordBatch, RustAnnError> {
+    let dim = index.dim;
+    let mut ids: Vec<i64> = Vec::new();
+    let mut flat_vectors: Vec<f32> = Vec::new();
+    let mut slot_indices: Vec<usize> = Vec::new();
+
+    for (idx, entry) in index.entries.iter().enumerate() {
+        if let Some((id, vector, _sq_norm)) = entry {
+            ids.push(*id);
+            flat_vectors.extend_from_slice(vector);
+            slot_indices.push(idx);
+        }
+    }
+
+    let id_array: ArrayRef = Arc::new(Int64Array::from(ids));
+    let vector_values = Float32Array::from(flat_vectors);
+    let vector_field = Arc::new(Field::new("item", DataType::Float32, false));
+    let vector_array: ArrayRef = Arc::new(
+        arrow::array::FixedSizeListArray::try_new(
+            vector_field.clone(),
+            dim as i32,
+            Arc::new(vector_values),
+            None,
+        )
+        .map_err(|e| RustAnnError::other_err(format!("failed to build vector column: {e}"), e))?,
+    );
+
+    let mut fields = vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new(
+            "vector",
+            DataType::FixedSizeList(vector_field, dim as i32),
+            false,
+        ),
+    ];
+    let mut columns: Vec<ArrayRef> = vec![id_array, vector_array];
+
+    if let (Some(schema), Some(metadata_columns)) = (&index.metadata_schema, &index.metadata_columns) {
+        for (field_name, field_type) in schema {
+            let Some(col) = metadata_columns.get(field_name) else {
+                continue;
+            };
+            let (data_type, array) = metadata_column_to_array(field_type, col, &slot_indices);
+            fields.push(Field::new(field_name.clone(), data_type, false));
+            columns.push(array);
+        }
+    }
+
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+        .map_err(|e| RustAnnError::other_err(format!("failed to build record batch: {e}"), e))
+}
+
+fn metadata_column_to_array(
+    field_type: &MetadataType,
+    col: &[MetadataValue],
+    slot_indices: &[usize],
+) -> (DataType, ArrayRef) {
+    match field_type {
+        MetadataType::Int => {
+            let values: Vec<i64> = slot_indices
+                .iter()
+                .map(|&i| match col.get(i) {
+                    Some(MetadataValue::Int(v)) => *v,
+                    _ => 0,
+                })
+                .collect();
+            (DataType::Int64, Arc::new(Int64Array::from(values)))
+        }
+        MetadataType::Timestamp => {
+            let values: Vec<i64> = slot_indices
+                .iter()
+                .map(|&i| match col.get(i) {
+                    Some(MetadataValue::Timestamp(v)) => *v,
+                    _ => 0,
+                })
+                .collect();
+            (DataType::Int64, Arc::new(Int64Array::from(values)))
+        }
+        MetadataType::Float => {
+            let values: Vec<f64> = slot_indices
+                .iter()
+                .map(|&i| match col.get(i) {
+                    Some(MetadataValue::Float(v)) => *v,
+                    _ => 0.0,
+                })
+                .collect();
+            (DataType::Float64, Arc::new(Float64Array::from(values)))
+        }
+        MetadataType::String => {
+            let values: Vec<String> = slot_indices
+                .iter()
+                .map(|&i| match col.get(i) {
+                    Some(MetadataValue::String(v)) => v.clone(),
+                    _ => String::new(),
+                })
+                .collect();
+            (DataType::Utf8, Arc::new(StringArray::from(values)))
+        }
+        MetadataType::Tags => {
+            let mut builder = ListBuilder::new(StringBuilder::new());
+            for &i in slot_indices {
+                match col.get(i) {
+                    Some(MetadataValue::Tags(tags)) => {
+                        for tag in tags {
+                            builder.values().append_value(tag);
+                        }
+                        builder.append(true);
+                    }
+                    _ => builder.append(true),
+                }
+            }
+            let item_field = Arc::new(Field::new("item", DataType::Utf8, true));
+            (DataType::List(item_field), Arc::new(builder.finish()))
+        }
+    }
+}
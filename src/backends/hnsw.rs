@@ -1,33 +1,124 @@
 use hnsw_rs::prelude::*;
 use crate::backends::ann_backend::AnnBackend;
+use crate::hnsw_index::HnswConfig;
 use crate::metrics::Distance;
 use rust_annie_macros::py_annindex;
 
-/// HNSW backend implementation.
-/// For now, only supports Euclidean (L2) distance.
+/// L-infinity (Chebyshev) distance. `hnsw_rs` doesn't ship one, so this
+/// mirrors the hand-rolled formula `crate::metrics::chebyshev` uses.
+#[derive(Default, Clone, Copy)]
+pub struct DistChebyshev;
+
+impl Distance<f32> for DistChebyshev {
+    fn eval(&self, va: &[f32], vb: &[f32]) -> f32 {
+        va.iter().zip(vb).map(|(x, y)| (x - y).abs()).fold(0.0, f32::max)
+    }
+}
+
+/// `Hnsw` is generic over its distance metric, so supporting more than one at
+/// runtime means wrapping one instance built with the right type per metric
+/// rather than a single generic field — the same enum-of-variants shape
+/// `BackendEnum` already uses one layer up for its different backends.
+enum HnswInner {
+    L2(Hnsw<'static, f32, DistL2>),
+    Cosine(Hnsw<'static, f32, DistCosine>),
+    L1(Hnsw<'static, f32, DistL1>),
+    Linf(Hnsw<'static, f32, DistChebyshev>),
+}
+
+/// HNSW backend implementation, configurable via `HnswConfig` and dispatching
+/// on `Distance` instead of always building an L2 index.
 #[py_annindex(backend = "HNSW", distance = "Euclidean")]
 pub struct HnswIndex {
-    index: Hnsw<'static, f32, DistL2>,
+    index: HnswInner,
     dims: usize,
+    /// Search-time `ef` the configured `HnswConfig::ef_search` maps to;
+    /// `search` uses this instead of a hardcoded literal.
+    ef_search: usize,
+    /// Raw vectors in insertion order, kept only so `BackendEnum::save` can
+    /// persist something to rebuild from — `hnsw_rs`'s graph itself isn't
+    /// otherwise reachable for serialization.
+    vectors: Vec<Vec<f32>>,
 }
 
 impl HnswIndex {
-    pub fn new(dims: usize, _distance: Distance) -> Self {
-        let index = Hnsw::new(
-            16,     // M: number of bi-directional links
-            10_000, // max elements
-            16,     // ef_construction
-            200,    // ef_search
-            DistL2 {},
-        );
-        Self { index, dims }
+    pub fn new(dims: usize, distance: Distance) -> Self {
+        Self::new_with_config(dims, distance, HnswConfig::default())
+    }
+
+    /// Build with explicit construction parameters instead of the fixed
+    /// `M=16, ef_construction=16, ef_search=200, max_elements=10_000` this
+    /// backend used to hardcode, and dispatch `distance` to the matching
+    /// `hnsw_rs` distance type instead of always `DistL2`.
+    pub fn new_with_config(dims: usize, distance: Distance, config: HnswConfig) -> Self {
+        let index = match distance {
+            Distance::Cosine() => HnswInner::Cosine(Hnsw::new(
+                config.m,
+                config.max_elements,
+                config.ef_construction,
+                config.ef_search,
+                DistCosine {},
+            )),
+            Distance::Manhattan() => HnswInner::L1(Hnsw::new(
+                config.m,
+                config.max_elements,
+                config.ef_construction,
+                config.ef_search,
+                DistL1 {},
+            )),
+            Distance::Chebyshev() => HnswInner::Linf(Hnsw::new(
+                config.m,
+                config.max_elements,
+                config.ef_construction,
+                config.ef_search,
+                DistChebyshev,
+            )),
+            // Every other metric (Euclidean, Minkowski, Hamming, Jaccard,
+            // Angular, Canberra, Custom) falls back to L2 — `hnsw_rs` has no
+            // built-in for most of those, and Euclidean is this backend's
+            // documented default.
+            _ => HnswInner::L2(Hnsw::new(
+                config.m,
+                config.max_elements,
+                config.ef_construction,
+                config.ef_search,
+                DistL2 {},
+            )),
+        };
+        Self { index, dims, ef_search: config.ef_search, vectors: Vec::new() }
+    }
+
+    pub fn dims(&self) -> usize {
+        self.dims
+    }
+
+    /// The distance this index was built with, recovered from which
+    /// `HnswInner` variant it's holding. Used by `BackendEnum::save` to
+    /// record the configured metric in its persistence header.
+    pub fn distance(&self) -> Distance {
+        match &self.index {
+            HnswInner::L2(_) => Distance::Euclidean(),
+            HnswInner::Cosine(_) => Distance::Cosine(),
+            HnswInner::L1(_) => Distance::Manhattan(),
+            HnswInner::Linf(_) => Distance::Chebyshev(),
+        }
+    }
+
+    pub fn vectors(&self) -> &[Vec<f32>] {
+        &self.vectors
     }
 }
 
 impl AnnBackend for HnswIndex {
     fn add(&mut self, vector: Vec<f32>) {
-        let id = self.index.get_nb_point();
-        self.index.insert((&vector, id));
+        let id = self.len();
+        match &mut self.index {
+            HnswInner::L2(idx) => idx.insert((&vector, id)),
+            HnswInner::Cosine(idx) => idx.insert((&vector, id)),
+            HnswInner::L1(idx) => idx.insert((&vector, id)),
+            HnswInner::Linf(idx) => idx.insert((&vector, id)),
+        }
+        self.vectors.push(vector);
     }
 
     fn add_batch(&mut self, vectors: Vec<Vec<f32>>, start_id: usize) {
@@ -40,14 +131,21 @@ impl AnnBackend for HnswIndex {
     }
 
     fn search(&self, query: &[f32], k: usize) -> Vec<(usize, f32)> {
-        self.index
-            .search(query, k, 50)
-            .into_iter()
-            .map(|n| (n.d_id as usize, n.distance))
-            .collect()
+        let neighbours = match &self.index {
+            HnswInner::L2(idx) => idx.search(query, k, self.ef_search),
+            HnswInner::Cosine(idx) => idx.search(query, k, self.ef_search),
+            HnswInner::L1(idx) => idx.search(query, k, self.ef_search),
+            HnswInner::Linf(idx) => idx.search(query, k, self.ef_search),
+        };
+        neighbours.into_iter().map(|n| (n.d_id as usize, n.distance)).collect()
     }
 
     fn len(&self) -> usize {
-        self.index.get_nb_point() as usize
+        match &self.index {
+            HnswInner::L2(idx) => idx.get_nb_point() as usize,
+            HnswInner::Cosine(idx) => idx.get_nb_point() as usize,
+            HnswInner::L1(idx) => idx.get_nb_point() as usize,
+            HnswInner::Linf(idx) => idx.get_nb_point() as usize,
+        }
     }
 }
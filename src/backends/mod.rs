@@ -2,29 +2,55 @@ pub mod ann_backend;
 pub mod brute;
 pub mod hnsw;
 pub mod gpu;
+pub mod quantized;
 
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Cursor, Read, Write};
+
+use pyo3::PyResult;
+
+use crate::errors::RustAnnError;
+use crate::gpu::Precision;
 use crate::metrics::Distance;
+use crate::path_validation::validate_path_secure;
+use crate::storage::{crc32, io_err, read_f32, read_metric, read_u32, read_u64, write_metric};
 use ann_backend::AnnBackend;
 use brute::BruteForceIndex;
 use hnsw::HnswIndex;
 use gpu::GpuIndex;
+use quantized::QuantizedIndex;
+
+const BACKEND_MAGIC: &[u8; 8] = b"ANNBKD01";
+const BACKEND_FORMAT_VERSION: u32 = 2;
 
 /// Enum to wrap the different backends under a single type.
 pub enum BackendEnum {
     Brute(BruteForceIndex),
     Hnsw(HnswIndex),
     Gpu(GpuIndex),
+    Quantized(QuantizedIndex),
 }
 
 impl BackendEnum {
-    /// Create a new backend by type name.
+    /// Create a new backend by type name. `"quantized"` builds an `Int8`
+    /// quantized backend; use [`BackendEnum::new_quantized`] to pick
+    /// `Fp16` instead.
     pub fn new(backend_type: &str, dims: usize, distance: Distance) -> Self {
         match backend_type {
             "hnsw" => Self::Hnsw(HnswIndex::new(dims, distance)),
             "gpu" => Self::Gpu(GpuIndex::new(dims, distance)),
+            "quantized" => Self::new_quantized(dims, distance, Precision::Int8)
+                .expect("Int8 is always a supported QuantizedIndex precision"),
             _      => Self::Brute(BruteForceIndex::new(distance)),
         }
     }
+
+    /// Create a quantized backend at a specific [`Precision`] (`Int8` or
+    /// `Fp16`), surfacing an unsupported precision as a `PyErr` instead of
+    /// panicking.
+    pub fn new_quantized(dims: usize, distance: Distance, precision: Precision) -> PyResult<Self> {
+        Ok(Self::Quantized(QuantizedIndex::new(dims, distance, precision)?))
+    }
 }
 
 impl BackendEnum {
@@ -43,6 +69,234 @@ impl BackendEnum {
             None
         }
     }
+
+    /// A device-side batched insert closure, for callers (e.g. the GPU
+    /// benchmark) that want to hand over a large vector set in one call
+    /// instead of looping `add` and paying a host/device round trip per
+    /// vector. Only `Gpu` has a batched path; every other backend returns
+    /// `None` so the caller falls back to looping `add` itself.
+    pub fn batch_add_method(&mut self) -> Option<impl FnMut(Vec<Vec<f32>>) + '_> {
+        match self {
+            BackendEnum::Gpu(gpu) => Some(move |vectors: Vec<Vec<f32>>| gpu.add_batch(vectors)),
+            _ => None,
+        }
+    }
+
+    fn backend_type(&self) -> &'static str {
+        match self {
+            BackendEnum::Brute(_) => "brute",
+            BackendEnum::Hnsw(_) => "hnsw",
+            BackendEnum::Gpu(_) => "gpu",
+            BackendEnum::Quantized(_) => "quantized",
+        }
+    }
+
+    fn backend_tag(&self) -> u8 {
+        match self {
+            BackendEnum::Brute(_) => 0,
+            BackendEnum::Hnsw(_) => 1,
+            BackendEnum::Gpu(_) => 2,
+            BackendEnum::Quantized(_) => 3,
+        }
+    }
+
+    fn backend_type_for_tag(tag: u8) -> Option<&'static str> {
+        match tag {
+            0 => Some("brute"),
+            1 => Some("hnsw"),
+            2 => Some("gpu"),
+            3 => Some("quantized"),
+            _ => None,
+        }
+    }
+
+    fn dims(&self) -> usize {
+        match self {
+            BackendEnum::Brute(b) => b.dims(),
+            BackendEnum::Hnsw(h) => h.dims(),
+            BackendEnum::Gpu(g) => g.dims(),
+            BackendEnum::Quantized(q) => q.dims(),
+        }
+    }
+
+    fn distance(&self) -> Distance {
+        match self {
+            BackendEnum::Brute(b) => b.distance(),
+            BackendEnum::Hnsw(h) => h.distance(),
+            BackendEnum::Gpu(g) => g.distance(),
+            BackendEnum::Quantized(q) => q.distance(),
+        }
+    }
+
+    /// Raw `f32` vectors backing every variant except `Quantized`, which
+    /// doesn't retain them (that's the point) — `save` dequantizes that
+    /// variant on demand via `QuantizedIndex::dequantized_vectors` instead
+    /// of calling this.
+    fn vectors(&self) -> &[Vec<f32>] {
+        match self {
+            BackendEnum::Brute(b) => b.vectors(),
+            BackendEnum::Hnsw(h) => h.vectors(),
+            BackendEnum::Gpu(g) => g.vectors(),
+            BackendEnum::Quantized(_) => {
+                unreachable!("Quantized backends are serialized via dequantized_vectors(), not vectors()")
+            }
+        }
+    }
+
+    /// Persist this backend to `path`: a header recording the format
+    /// version, backend discriminant, dimensionality and distance metric,
+    /// followed by a length-prefixed, CRC32-checksummed body holding every
+    /// inserted vector in order, so `load` can rebuild an equivalent backend
+    /// by replaying them through `add`. `path` is routed through
+    /// `validate_path_secure` before anything touches the filesystem, the
+    /// same defense `AnnIndex::save` relies on.
+    pub fn save(&self, path: &str) -> PyResult<()> {
+        let validated = validate_path_secure(path)?;
+        (|| -> Result<(), RustAnnError> {
+            let file = File::create(&validated).map_err(io_err)?;
+            let mut w = BufWriter::new(file);
+
+            w.write_all(BACKEND_MAGIC).map_err(io_err)?;
+            w.write_all(&BACKEND_FORMAT_VERSION.to_le_bytes()).map_err(io_err)?;
+            w.write_all(&[self.backend_tag()]).map_err(io_err)?;
+            w.write_all(&(self.dims() as u64).to_le_bytes()).map_err(io_err)?;
+            write_metric(&mut w, &self.distance())?;
+
+            // `Quantized` doesn't retain raw `f32` vectors (that's the whole
+            // point of the backend), so its body is built by dequantizing on
+            // the fly rather than borrowing through `vectors()`.
+            let owned_dequantized;
+            let vectors: &[Vec<f32>] = if let BackendEnum::Quantized(q) = self {
+                owned_dequantized = q.dequantized_vectors();
+                &owned_dequantized
+            } else {
+                self.vectors()
+            };
+
+            let mut body = Cursor::new(Vec::new());
+            body.write_all(&(vectors.len() as u64).to_le_bytes()).map_err(io_err)?;
+            for vector in vectors {
+                for x in vector {
+                    body.write_all(&x.to_le_bytes()).map_err(io_err)?;
+                }
+            }
+            let body = body.into_inner();
+
+            w.write_all(&(body.len() as u64).to_le_bytes()).map_err(io_err)?;
+            w.write_all(&crc32(&body).to_le_bytes()).map_err(io_err)?;
+            w.write_all(&body).map_err(io_err)?;
+            w.flush().map_err(io_err)
+        })()
+        .map_err(|e| e.into_pyerr())
+    }
+
+    /// Load a backend previously written by `save`. Reads the body as one
+    /// length-prefixed, checksummed block — surfacing a truncated file as
+    /// [`RustAnnError::UnexpectedEof`] and a bit-flipped one as
+    /// [`RustAnnError::Corrupt`] before any vector in it is trusted — then
+    /// rebuilds a fresh `backend_type`/`dims`/`distance` backend and replays
+    /// every stored vector through `add`. Refuses to load a file whose
+    /// header doesn't match the configuration the caller requested rather
+    /// than silently handing back a mismatched backend. The on-disk body is
+    /// always plain `f32` vectors, so a `"quantized"` file is rebuilt as an
+    /// `Int8` backend regardless of which precision originally wrote it —
+    /// the format doesn't carry that bit today.
+    pub fn load(path: &str, backend_type: &str, dims: usize, distance: Distance) -> PyResult<Self> {
+        let validated = validate_path_secure(path)?;
+        (|| -> Result<Self, RustAnnError> {
+            let file = File::open(&validated).map_err(io_err)?;
+            let mut r = BufReader::new(file);
+
+            let mut magic = [0u8; 8];
+            r.read_exact(&mut magic).map_err(io_err)?;
+            if &magic != BACKEND_MAGIC {
+                return Err(RustAnnError::Corrupt("not an Annie backend file (bad magic)".to_string()));
+            }
+
+            let format_version = read_u32(&mut r)?;
+            if format_version != BACKEND_FORMAT_VERSION {
+                return Err(RustAnnError::Io(
+                    format!(
+                        "unsupported backend format version {} (expected {})",
+                        format_version, BACKEND_FORMAT_VERSION
+                    ),
+                    None,
+                ));
+            }
+
+            let mut tag = [0u8; 1];
+            r.read_exact(&mut tag).map_err(io_err)?;
+            let stored_type = Self::backend_type_for_tag(tag[0])
+                .ok_or_else(|| RustAnnError::Corrupt(format!("unknown backend tag {}", tag[0])))?;
+            if stored_type != backend_type {
+                return Err(RustAnnError::Io(
+                    format!(
+                        "backend type mismatch: file holds '{}', requested '{}'",
+                        stored_type, backend_type
+                    ),
+                    None,
+                ));
+            }
+
+            let stored_dims = read_u64(&mut r)? as usize;
+            if stored_dims != dims {
+                return Err(RustAnnError::Io(
+                    format!(
+                        "dimensionality mismatch: file holds {}, requested {}",
+                        stored_dims, dims
+                    ),
+                    None,
+                ));
+            }
+
+            let stored_metric = read_metric(&mut r)?;
+            if write_metric_tag(&stored_metric) != write_metric_tag(&distance) {
+                return Err(RustAnnError::Io(
+                    "distance metric mismatch between file and requested configuration".to_string(),
+                    None,
+                ));
+            }
+
+            let body_len = read_u64(&mut r)? as usize;
+            let body_crc32 = read_u32(&mut r)?;
+            let mut body = vec![0u8; body_len];
+            r.read_exact(&mut body).map_err(io_err)?;
+            if crc32(&body) != body_crc32 {
+                return Err(RustAnnError::Corrupt("body failed CRC32 verification".to_string()));
+            }
+            let mut body = Cursor::new(body);
+
+            let vector_count = read_u64(&mut body)?;
+            let mut backend = Self::new(backend_type, dims, distance);
+            for _ in 0..vector_count {
+                let mut vector = Vec::with_capacity(dims);
+                for _ in 0..dims {
+                    vector.push(read_f32(&mut body)?);
+                }
+                backend.add(vector);
+            }
+            Ok(backend)
+        })()
+        .map_err(|e| e.into_pyerr())
+    }
+}
+
+/// The metric tag `write_metric` would write, without needing `Distance` to
+/// implement `PartialEq` — used by `BackendEnum::load` to compare the
+/// stored metric against the one the caller requested.
+fn write_metric_tag(metric: &Distance) -> u8 {
+    match metric {
+        Distance::Euclidean() => 0,
+        Distance::Cosine() => 1,
+        Distance::Manhattan() => 2,
+        Distance::Chebyshev() => 3,
+        Distance::Minkowski(_) => 4,
+        Distance::Hamming() => 5,
+        Distance::Jaccard() => 6,
+        Distance::Angular() => 7,
+        Distance::Canberra() => 8,
+        Distance::Custom(_) => 9,
+    }
 }
 
 impl AnnBackend for BackendEnum {
@@ -50,6 +304,8 @@ impl AnnBackend for BackendEnum {
         match self {
             BackendEnum::Brute(b) => b.add(vector),
             BackendEnum::Hnsw(h)  => h.add(vector),
+            BackendEnum::Gpu(g)   => g.add(vector),
+            BackendEnum::Quantized(q) => q.add(vector),
         }
     }
 
@@ -57,6 +313,8 @@ impl AnnBackend for BackendEnum {
         match self {
             BackendEnum::Brute(b) => b.search(query, k),
             BackendEnum::Hnsw(h)  => h.search(query, k),
+            BackendEnum::Gpu(g)   => g.search(query, k),
+            BackendEnum::Quantized(q) => q.search(query, k),
         }
     }
 
@@ -64,6 +322,8 @@ impl AnnBackend for BackendEnum {
         match self {
             BackendEnum::Brute(b) => b.len(),
             BackendEnum::Hnsw(h)  => h.len(),
+            BackendEnum::Gpu(g)   => g.len(),
+            BackendEnum::Quantized(q) => q.len(),
         }
     }
 }
@@ -0,0 +1,203 @@
+use half::f16;
+
+use crate::backends::ann_backend::AnnBackend;
+use crate::errors::RustAnnError;
+use crate::gpu::Precision;
+use crate::metrics::Distance;
+
+/// One inserted vector's quantized codes, plus whatever this backend's
+/// `Precision` needs to dequantize them back to `f32` for distance
+/// computation. `Int8` additionally carries the per-vector scale factor
+/// learned at insert time (`127.0 / max(|x_i|)`), so the clamp range tracks
+/// each vector's own magnitude instead of a fixed `127.0`.
+enum QuantizedVector {
+    Int8 { codes: Vec<i8>, scale: f32 },
+    Fp16 { codes: Vec<f16> },
+}
+
+impl QuantizedVector {
+    fn dequantize(&self) -> Vec<f32> {
+        match self {
+            QuantizedVector::Int8 { codes, scale } => codes.iter().map(|&c| c as f32 / scale).collect(),
+            QuantizedVector::Fp16 { codes } => codes.iter().map(|c| c.to_f32()).collect(),
+        }
+    }
+}
+
+/// `AnnBackend` that stores vectors as `Int8` or `Fp16` codes instead of raw
+/// `f32`, cutting memory ~4x/2x at the cost of dequantizing on each distance
+/// computation. `Precision::Fp32`/`Q8_0`/`Q4_0` aren't meaningful encodings
+/// here (the first defeats the point, the block formats are GPU-kernel
+/// specific), so `new` rejects them up front instead of silently
+/// misbehaving later.
+pub struct QuantizedIndex {
+    precision: Precision,
+    distance: Distance,
+    dims: usize,
+    vectors: Vec<QuantizedVector>,
+}
+
+impl QuantizedIndex {
+    /// Build a `QuantizedIndex`, rejecting `Fp32`/`Q8_0`/`Q4_0` as a
+    /// `RustAnnError` instead of panicking, so a caller that threads a
+    /// user-chosen `Precision` through from Python gets back a normal
+    /// `PyErr` rather than an abort.
+    pub fn new(dims: usize, distance: Distance, precision: Precision) -> Result<Self, RustAnnError> {
+        if !matches!(precision, Precision::Int8 | Precision::Fp16) {
+            return Err(RustAnnError::Message(format!(
+                "QuantizedIndex only supports Int8 or Fp16 precision, got {:?}",
+                precision
+            )));
+        }
+        Ok(Self { precision, distance, dims, vectors: Vec::new() })
+    }
+
+    pub fn dims(&self) -> usize {
+        self.dims
+    }
+
+    pub fn distance(&self) -> Distance {
+        self.distance
+    }
+
+    /// Dequantize every stored vector back to `f32`. Used by
+    /// [`crate::backends::BackendEnum::save`], which persists `f32` vectors
+    /// for every backend — built on demand rather than cached, since caching
+    /// it defeats the point of this backend.
+    pub(crate) fn dequantized_vectors(&self) -> Vec<Vec<f32>> {
+        self.vectors.iter().map(|v| v.dequantize()).collect()
+    }
+
+    /// Encode a vector at this index's configured precision. A non-finite
+    /// component (`NaN`, `+-inf`) always encodes to the zero code instead of
+    /// propagating into the learned scale or a later distance computation.
+    fn encode(&self, vector: &[f32]) -> QuantizedVector {
+        match self.precision {
+            Precision::Int8 => {
+                let max_abs = vector.iter().copied().filter(|x| x.is_finite()).fold(0.0f32, |acc, x| acc.max(x.abs()));
+                let scale = if max_abs > 0.0 { 127.0 / max_abs } else { 1.0 };
+                let codes = vector
+                    .iter()
+                    .map(|&x| if !x.is_finite() { 0i8 } else { (x * scale).clamp(-128.0, 127.0) as i8 })
+                    .collect();
+                QuantizedVector::Int8 { codes, scale }
+            }
+            Precision::Fp16 => {
+                let codes = vector
+                    .iter()
+                    .map(|&x| if x.is_finite() { f16::from_f32(x) } else { f16::from_f32(0.0) })
+                    .collect();
+                QuantizedVector::Fp16 { codes }
+            }
+            _ => unreachable!("validated in `new`"),
+        }
+    }
+
+    /// Dequantize `stored` and compute its distance to `query` under this
+    /// index's configured metric.
+    fn distance_to(&self, query: &[f32], stored: &QuantizedVector) -> f32 {
+        let dequantized = stored.dequantize();
+        match self.distance {
+            Distance::Euclidean() => {
+                query.iter().zip(&dequantized).map(|(x, y)| (x - y).powi(2)).sum::<f32>().sqrt()
+            }
+            Distance::Cosine() | Distance::Angular() => {
+                let dot: f32 = query.iter().zip(&dequantized).map(|(x, y)| x * y).sum();
+                let norm_q: f32 = query.iter().map(|x| x.powi(2)).sum::<f32>().sqrt();
+                let norm_s: f32 = dequantized.iter().map(|x| x.powi(2)).sum::<f32>().sqrt();
+                if norm_q == 0.0 || norm_s == 0.0 {
+                    1.0
+                } else {
+                    (1.0 - dot / (norm_q * norm_s)).max(0.0)
+                }
+            }
+            Distance::Manhattan() => query.iter().zip(&dequantized).map(|(x, y)| (x - y).abs()).sum(),
+            Distance::Chebyshev() => query.iter().zip(&dequantized).map(|(x, y)| (x - y).abs()).fold(0.0, f32::max),
+            // Minkowski/Hamming/Jaccard/Canberra/Custom aren't meaningful
+            // without the parameter or registry lookup those variants carry;
+            // fall back to Euclidean rather than panicking mid-search.
+            _ => query.iter().zip(&dequantized).map(|(x, y)| (x - y).powi(2)).sum::<f32>().sqrt(),
+        }
+    }
+}
+
+impl AnnBackend for QuantizedIndex {
+    fn add(&mut self, vector: Vec<f32>) {
+        let encoded = self.encode(&vector);
+        self.vectors.push(encoded);
+    }
+
+    fn add_batch(&mut self, vectors: Vec<Vec<f32>>, _start_id: usize) {
+        for v in vectors {
+            self.add(v);
+        }
+    }
+
+    fn search(&self, query: &[f32], k: usize) -> Vec<(usize, f32)> {
+        let mut scored: Vec<(usize, f32)> =
+            self.vectors.iter().enumerate().map(|(id, v)| (id, self.distance_to(query, v))).collect();
+        scored.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+        scored.truncate(k);
+        scored
+    }
+
+    fn len(&self) -> usize {
+        self.vectors.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_rejects_unsupported_precisions() {
+        assert!(QuantizedIndex::new(4, Distance::Euclidean(), Precision::Fp32).is_err());
+        assert!(QuantizedIndex::new(4, Distance::Euclidean(), Precision::Q8_0).is_err());
+        assert!(QuantizedIndex::new(4, Distance::Euclidean(), Precision::Q4_0).is_err());
+        assert!(QuantizedIndex::new(4, Distance::Euclidean(), Precision::Int8).is_ok());
+        assert!(QuantizedIndex::new(4, Distance::Euclidean(), Precision::Fp16).is_ok());
+    }
+
+    #[test]
+    fn int8_round_trip_is_approximately_lossless() {
+        let mut index = QuantizedIndex::new(3, Distance::Euclidean(), Precision::Int8).unwrap();
+        let original = vec![1.0, -2.0, 0.5];
+        index.add(original.clone());
+        let dequantized = &index.dequantized_vectors()[0];
+        for (a, b) in original.iter().zip(dequantized) {
+            assert!((a - b).abs() < 0.05, "expected {} ~= {}", a, b);
+        }
+    }
+
+    #[test]
+    fn fp16_round_trip_is_approximately_lossless() {
+        let mut index = QuantizedIndex::new(3, Distance::Euclidean(), Precision::Fp16).unwrap();
+        let original = vec![1.0, -2.0, 0.5];
+        index.add(original.clone());
+        let dequantized = &index.dequantized_vectors()[0];
+        for (a, b) in original.iter().zip(dequantized) {
+            assert!((a - b).abs() < 0.01, "expected {} ~= {}", a, b);
+        }
+    }
+
+    #[test]
+    fn nan_and_infinite_components_encode_to_zero() {
+        let mut int8_index = QuantizedIndex::new(3, Distance::Euclidean(), Precision::Int8).unwrap();
+        int8_index.add(vec![f32::NAN, f32::INFINITY, f32::NEG_INFINITY]);
+        assert_eq!(int8_index.dequantized_vectors()[0], vec![0.0, 0.0, 0.0]);
+
+        let mut fp16_index = QuantizedIndex::new(3, Distance::Euclidean(), Precision::Fp16).unwrap();
+        fp16_index.add(vec![f32::NAN, f32::INFINITY, f32::NEG_INFINITY]);
+        assert_eq!(fp16_index.dequantized_vectors()[0], vec![0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn search_returns_nearest_by_configured_metric() {
+        let mut index = QuantizedIndex::new(2, Distance::Euclidean(), Precision::Fp16).unwrap();
+        index.add(vec![0.0, 0.0]);
+        index.add(vec![10.0, 10.0]);
+        let results = index.search(&[0.1, 0.1], 1);
+        assert_eq!(results[0].0, 0);
+    }
+}
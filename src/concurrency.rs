@@ -12,7 +12,8 @@ fn get_read_lock<'a>(lock: &'a Arc<RwLock<AnnIndex>>) -> Result<std::sync::RwLoc
     lock.read().map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to acquire read lock: {}", e)))
 }
 
-use std::sync::{Arc, RwLock};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock, Weak};
 use pyo3::prelude::*;
 use numpy::{PyReadonlyArray1, PyReadonlyArray2};
 
@@ -20,9 +21,64 @@ use crate::index::AnnIndex;
 use crate::metrics::Distance;
 
 /// A thread-safe, Python-visible wrapper around [`AnnIndex`].
+///
+/// Mutation (`add`/`remove`/`update`/`compact`) still goes through `inner`'s
+/// `RwLock`. Reads (`search`/`search_batch`/`search_at`) instead run against
+/// an MVCC snapshot: `current` holds the latest published generation, and
+/// each mutation publishes a fresh one built by cloning the mutated index,
+/// so searches never hold `inner`'s lock for the duration of a query.
 #[pyclass]
 pub struct ThreadSafeAnnIndex {
     inner: Arc<RwLock<AnnIndex>>,
+    current: RwLock<Arc<AnnIndex>>,
+    /// Older generations, kept reachable by version only as long as some
+    /// `search_at` caller still holds a strong reference to them.
+    history: std::sync::Mutex<HashMap<u64, Weak<AnnIndex>>>,
+}
+
+impl ThreadSafeAnnIndex {
+    /// Internal constructor for testing: wraps an existing Arc<RwLock<AnnIndex>>.
+    pub fn from_arc(inner: Arc<RwLock<AnnIndex>>) -> Self {
+        let snapshot = {
+            let guard = inner.read().unwrap();
+            guard.snapshot()
+        };
+        let version = snapshot.version();
+        let mut history = HashMap::new();
+        history.insert(version, Arc::downgrade(&snapshot));
+        Self {
+            inner,
+            current: RwLock::new(snapshot),
+            history: std::sync::Mutex::new(history),
+        }
+    }
+
+    /// Publish a fresh snapshot of `inner`'s current state as the new
+    /// generation, pruning any older generations no reader still holds.
+    fn publish_snapshot(&self) {
+        let snapshot = {
+            let guard = get_read_lock(&self.inner).expect("lock poisoned");
+            guard.snapshot()
+        };
+        let version = snapshot.version();
+        *self.current.write().unwrap() = snapshot.clone();
+        let mut history = self.history.lock().unwrap();
+        history.retain(|_, weak| weak.upgrade().is_some());
+        history.insert(version, Arc::downgrade(&snapshot));
+    }
+
+    /// The most recently published snapshot, shared via `Arc` so callers can
+    /// search it without holding any lock.
+    fn snapshot(&self) -> Arc<AnnIndex> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// The snapshot for a specific version, if it's still retained (either
+    /// because it's the current generation or another reader is holding it
+    /// via an earlier `search_at` call).
+    fn snapshot_at(&self, version: u64) -> Option<Arc<AnnIndex>> {
+        self.history.lock().unwrap().get(&version).and_then(Weak::upgrade)
+    }
 }
 
 #[pymethods]
@@ -31,9 +87,7 @@ impl ThreadSafeAnnIndex {
     #[new]
     pub fn new(dim: usize, metric: Distance) -> PyResult<Self> {
         let idx = AnnIndex::new(dim, metric)?;
-        Ok(ThreadSafeAnnIndex {
-            inner: Arc::new(RwLock::new(idx)),
-        })
+        Ok(Self::from_arc(Arc::new(RwLock::new(idx))))
     }
 
     /// Add vectors with IDs.
@@ -43,26 +97,38 @@ impl ThreadSafeAnnIndex {
         data: PyReadonlyArray2<f32>,
         ids: PyReadonlyArray1<i64>,
     ) -> PyResult<()> {
-    let mut guard = get_write_lock(&self.inner)?;
-        guard.add(py, data, ids)
+        let mut guard = get_write_lock(&self.inner)?;
+        guard.add(py, data, ids)?;
+        drop(guard);
+        self.publish_snapshot();
+        Ok(())
     }
 
     /// Remove by ID.
     pub fn remove(&self, _py: Python, ids: Vec<i64>) -> PyResult<()> {
-    let mut guard = get_write_lock(&self.inner)?;
-        guard.remove(ids)
+        let mut guard = get_write_lock(&self.inner)?;
+        guard.remove(ids)?;
+        drop(guard);
+        self.publish_snapshot();
+        Ok(())
     }
 
     pub fn update(&self, _py: Python, id: i64, vector: Vec<f32>) -> PyResult<()> {
-    let mut guard = get_write_lock(&self.inner)?;
-        guard.update(id, vector)
+        let mut guard = get_write_lock(&self.inner)?;
+        guard.update(id, vector)?;
+        drop(guard);
+        self.publish_snapshot();
+        Ok(())
     }
 
     pub fn compact(&self, _py: Python) -> PyResult<()> {
-    let mut guard = get_write_lock(&self.inner)?;
-        guard.compact()
+        let mut guard = get_write_lock(&self.inner)?;
+        guard.compact()?;
+        drop(guard);
+        self.publish_snapshot();
+        Ok(())
     }
-    
+
     pub fn version(&self, _py: Python) -> u64 {
         match get_read_lock(&self.inner) {
             Ok(guard) => guard.version(),
@@ -70,47 +136,60 @@ impl ThreadSafeAnnIndex {
         }
     }
 
-    /// Single-vector k-NN search.
+    /// Single-vector k-NN search against the latest snapshot. Holds no lock
+    /// on `inner` for the duration of the query, so it can't starve or be
+    /// starved by concurrent `add`/`update`/`remove`/`compact` calls.
     pub fn search(
         &self,
         py: Python,
         query: PyReadonlyArray1<f32>,
         k: usize,
     ) -> PyResult<(PyObject, PyObject)> {
-    let guard = get_read_lock(&self.inner)?;
-        guard.search(py, query, k, None)
+        let snapshot = self.snapshot();
+        snapshot.search(py, query, k, None)
     }
 
-    /// Batch k-NN search.
+    /// Batch k-NN search against the latest snapshot (see [`search`]).
     pub fn search_batch(
         &self,
         py: Python,
         data: PyReadonlyArray2<f32>,
         k: usize,
     ) -> PyResult<(PyObject, PyObject)> {
-    let guard = get_read_lock(&self.inner)?;
-        guard.search_batch(py, data, k, None)
+        let snapshot = self.snapshot();
+        snapshot.search_batch(py, data, k, None)
+    }
+
+    /// Single-vector k-NN search pinned to a specific `version`, so repeated
+    /// calls see reproducible results even while writers are in progress.
+    /// Fails if that generation is no longer retained (too old, and no other
+    /// caller is still holding it).
+    pub fn search_at(
+        &self,
+        py: Python,
+        version: u64,
+        query: PyReadonlyArray1<f32>,
+        k: usize,
+    ) -> PyResult<(PyObject, PyObject)> {
+        let snapshot = self.snapshot_at(version).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!(
+                "snapshot for version {} is no longer available",
+                version
+            ))
+        })?;
+        snapshot.search(py, query, k, None)
     }
 
     /// Save to disk.
     pub fn save(&self, _py: Python, path: &str) -> PyResult<()> {
-    let guard = get_read_lock(&self.inner)?;
-        guard.save(path)
+        let snapshot = self.snapshot();
+        snapshot.save(path)
     }
 
     /// Load and wrap.
     #[staticmethod]
     pub fn load(_py: Python, path: &str) -> PyResult<Self> {
         let idx = AnnIndex::load(path)?;
-        Ok(ThreadSafeAnnIndex {
-            inner: Arc::new(RwLock::new(idx)),
-        })
-    }
-}
-
-impl ThreadSafeAnnIndex {
-    /// Internal constructor for testing: wraps an existing Arc<RwLock<AnnIndex>>.
-    pub fn from_arc(inner: Arc<RwLock<AnnIndex>>) -> Self {
-        Self { inner }
+        Ok(Self::from_arc(Arc::new(RwLock::new(idx))))
     }
 }
@@ -4,13 +4,31 @@ use std::sync::Mutex;
 use pyo3::prelude::*;
 use numpy::{IntoPyArray, PyArray1};
 
-use crate::errors::DistanceRegistryError;
+use crate::errors::{DistanceRegistryError, RustAnnError};
 
 /// Trait for distance functions that can be registered and used by the index.
 pub trait DistanceFunction: Send + Sync {
     fn distance(&self, a: &[f32], b: &[f32]) -> f32;
     fn name(&self) -> &str;
     fn clone_boxed(&self) -> Box<dyn DistanceFunction>;
+
+    /// Whether this function calls back into Python. Callers that would
+    /// otherwise run it under `par_iter` should fall back to a sequential
+    /// scan instead, since every call reacquires the GIL.
+    fn is_python(&self) -> bool {
+        false
+    }
+
+    /// Like [`DistanceFunction::distance`], but for implementations that
+    /// call into Python, surfaces the real exception a failing callback
+    /// raised — class and traceback intact, via [`RustAnnError::Callback`]
+    /// — instead of collapsing the failure to `f32::MAX`. The default
+    /// implementation just wraps the infallible `distance`, since every
+    /// built-in distance here is infallible; only [`PythonDistanceFunction`]
+    /// overrides it.
+    fn try_distance(&self, a: &[f32], b: &[f32]) -> Result<f32, RustAnnError> {
+        Ok(self.distance(a, b))
+    }
 }
 
 impl Clone for Box<dyn DistanceFunction> {
@@ -128,21 +146,7 @@ impl PythonDistanceFunction {
 
 impl DistanceFunction for PythonDistanceFunction {
     fn distance(&self, a: &[f32], b: &[f32]) -> f32 {
-        Python::with_gil(|py| {
-            let a_py = match a.into_pyobject(py) {
-                Ok(obj) => obj,
-                Err(_) => return f32::MAX,
-            };
-            let b_py = match b.into_pyobject(py) {
-                Ok(obj) => obj,
-                Err(_) => return f32::MAX,
-            };
-
-            match self.python_func.call1(py, (a_py, b_py)) {
-                Ok(result) => result.extract::<f32>(py).unwrap_or(f32::MAX),
-                Err(_) => f32::MAX,
-            }
-        })
+        self.try_distance(a, b).unwrap_or(f32::MAX)
     }
 
     fn name(&self) -> &str {
@@ -152,6 +156,27 @@ impl DistanceFunction for PythonDistanceFunction {
     fn clone_boxed(&self) -> Box<dyn DistanceFunction> {
         Box::new(self.clone())
     }
+
+    fn is_python(&self) -> bool {
+        true
+    }
+
+    fn try_distance(&self, a: &[f32], b: &[f32]) -> Result<f32, RustAnnError> {
+        Python::with_gil(|py| {
+            let a_py = a
+                .into_pyobject(py)
+                .map_err(|e| RustAnnError::Callback(e.into()))?;
+            let b_py = b
+                .into_pyobject(py)
+                .map_err(|e| RustAnnError::Callback(e.into()))?;
+
+            let result = self
+                .python_func
+                .call1(py, (a_py, b_py))
+                .map_err(RustAnnError::Callback)?;
+            result.extract::<f32>(py).map_err(RustAnnError::Callback)
+        })
+    }
 }
 
 /// Registry of distance functions.
@@ -1,10 +1,22 @@
 // src/errors.rs
+use std::error::Error as StdError;
 use std::sync::PoisonError;
 use pyo3::exceptions::{PyException, PyIOError, PyRuntimeError, PyValueError};
-use pyo3::PyErr;
+use pyo3::{PyErr, Python};
 use thiserror::Error;
 
+/// A boxed error source, used by the variants below that wrap a real
+/// underlying failure (as opposed to `Message`/`Dimension`/etc., which are
+/// raised directly by this crate's own logic and have nothing further to
+/// chain to, and `Callback`, which carries its Python exception whole).
+type BoxedSource = Box<dyn StdError + Send + Sync>;
+
 /// A standardized error type for the ANN library, with context and chaining.
+///
+/// Variants that wrap a real underlying cause (`Io`, `Other`,
+/// `UnexpectedEof`) carry it via `#[source]` so `std::error::Error::source`
+/// can walk the chain; `into_pyerr` formats that chain into the exception
+/// message and attaches the innermost cause as the Python `__cause__`.
 #[derive(Debug, Error)]
 pub enum RustAnnError {
     #[error("Lock poisoned")]
@@ -12,9 +24,17 @@ pub enum RustAnnError {
     #[error("{0}")]
     Message(String),
     #[error("I/O error: {0}")]
-    Io(String),
+    Io(String, #[source] Option<BoxedSource>),
+    /// A Python exception raised inside a user-supplied callback (e.g. a
+    /// custom distance function). Carries the real, captured `PyErr` —
+    /// class and traceback intact — rather than a flattened string, so
+    /// `into_pyerr` can hand the interpreter back the exact exception the
+    /// callback raised instead of a generic wrapper. The `PyErr` must be
+    /// captured and later unwrapped under the GIL; `PyErr` itself is
+    /// `Send + Sync` so holding one across threads between those two points
+    /// is sound.
     #[error("Callback error: {0}")]
-    Callback(String),
+    Callback(PyErr),
     #[error("Duplicate IDs: {0}")]
     DuplicateIds(String),
     #[error("Dimension error: {0}")]
@@ -28,7 +48,11 @@ pub enum RustAnnError {
     #[error("Minkowski error: {0}")]
     Minkowski(String),
     #[error("Other error: {0}")]
-    Other(String),
+    Other(String, #[source] Option<BoxedSource>),
+    #[error("Unexpected end of file: {0}")]
+    UnexpectedEof(String, #[source] Option<BoxedSource>),
+    #[error("Corrupt data: {0}")]
+    Corrupt(String),
 }
 
 #[derive(Debug, Error)]
@@ -46,22 +70,77 @@ impl RustAnnError {
         PyException::new_err(msg)
     }
     pub fn io_err(msg: impl Into<String>) -> RustAnnError {
-        RustAnnError::Io(msg.into())
+        RustAnnError::Io(msg.into(), None)
+    }
+
+    /// Like [`RustAnnError::io_err`], but attaches `source` as the
+    /// underlying cause, so it survives into `into_pyerr`'s formatted
+    /// "Caused by:" chain and Python `__cause__` instead of being discarded.
+    pub fn io_err_with_source(msg: impl Into<String>, source: impl StdError + Send + Sync + 'static) -> RustAnnError {
+        RustAnnError::Io(msg.into(), Some(Box::new(source)))
+    }
+
+    /// Like [`RustAnnError::io_err_with_source`], for the catch-all `Other`
+    /// variant.
+    pub fn other_err(msg: impl Into<String>, source: impl StdError + Send + Sync + 'static) -> RustAnnError {
+        RustAnnError::Other(msg.into(), Some(Box::new(source)))
+    }
+
+    /// This error together with its full `source()` chain, one
+    /// "Caused by:" line per link, so a caller sees every layer that
+    /// contributed to the failure instead of just the outermost message.
+    fn format_chain(&self) -> String {
+        let mut out = self.to_string();
+        let mut current = StdError::source(self);
+        while let Some(cause) = current {
+            out.push_str(&format!("\nCaused by: {cause}"));
+            current = cause.source();
+        }
+        out
+    }
+
+    /// The innermost error in this error's `source()` chain, if any, as a
+    /// `PyErr` — attached to the resulting exception as `__cause__` so
+    /// Python's `raise ... from` semantics survive the pyo3 boundary.
+    fn innermost_cause(&self) -> Option<PyErr> {
+        let mut current = StdError::source(self)?;
+        while let Some(next) = current.source() {
+            current = next;
+        }
+        Some(PyRuntimeError::new_err(current.to_string()))
     }
+
     pub fn into_pyerr(self) -> PyErr {
-        match self {
-            RustAnnError::Io(msg) => PyIOError::new_err(msg),
-            RustAnnError::Callback(msg) => PyRuntimeError::new_err(msg),
-            RustAnnError::DuplicateIds(msg) => PyValueError::new_err(msg),
-            RustAnnError::Dimension(msg) => PyValueError::new_err(msg),
-            RustAnnError::Allocation(msg) => PyRuntimeError::new_err(msg),
-            RustAnnError::EmptyIndex => PyValueError::new_err("Index is empty"),
-            RustAnnError::Reshape(msg) => PyValueError::new_err(msg),
-            RustAnnError::Minkowski(msg) => PyValueError::new_err(msg),
-            RustAnnError::Message(msg) => PyRuntimeError::new_err(msg),
-            RustAnnError::Other(msg) => PyRuntimeError::new_err(msg),
-            RustAnnError::LockPoisoned => PyRuntimeError::new_err("Lock poisoned"),
+        // A callback's `PyErr` already *is* the exception to raise, class
+        // and traceback intact — rewrapping it in a fresh exception would
+        // throw that traceback away, so hand it back unchanged instead of
+        // going through the generic formatting below.
+        let this = match self {
+            RustAnnError::Callback(err) => return err,
+            other => other,
+        };
+
+        let message = this.format_chain();
+        let cause = this.innermost_cause();
+        let err = Python::with_gil(|py| match this {
+            RustAnnError::Io(..) => crate::exceptions::backend_error(py, message),
+            RustAnnError::Callback(_) => unreachable!("handled above"),
+            RustAnnError::DuplicateIds(_) => crate::exceptions::duplicate_id_error(py, message),
+            RustAnnError::Dimension(_) => crate::exceptions::dimension_error(py, message),
+            RustAnnError::Allocation(_) => crate::exceptions::allocation_error(py, message),
+            RustAnnError::EmptyIndex => crate::exceptions::empty_index_error(py, message),
+            RustAnnError::Reshape(_) => PyValueError::new_err(message),
+            RustAnnError::Minkowski(_) => crate::exceptions::metric_error(py, message),
+            RustAnnError::Message(_) => PyRuntimeError::new_err(message),
+            RustAnnError::Other(..) => PyRuntimeError::new_err(message),
+            RustAnnError::LockPoisoned => PyRuntimeError::new_err(message),
+            RustAnnError::UnexpectedEof(..) => crate::exceptions::backend_error(py, message),
+            RustAnnError::Corrupt(_) => crate::exceptions::backend_error(py, message),
+        });
+        if let Some(cause) = cause {
+            Python::with_gil(|py| err.set_cause(py, Some(cause)));
         }
+        err
     }
 }
 
@@ -73,8 +152,11 @@ pub enum DistanceRegistryError {
     LockPoisoned,
     #[error("Distance registry not initialized")]
     RegistryNotInitialized,
+    /// Carries the real `PyErr` a distance callback raised, captured under
+    /// the GIL at the call site, rather than a flattened string — see
+    /// [`RustAnnError::Callback`] for the same rationale.
     #[error("Python call failed: {0}")]
-    PythonCallFailed(String),
+    PythonCallFailed(PyErr),
     #[error("Python value conversion failed: {0}")]
     PythonConversionFailed(String),
     #[error("Metric '{0}' not found")]
@@ -84,19 +166,29 @@ pub enum DistanceRegistryError {
 
 impl From<BackendCreationError> for PyErr {
     fn from(err: BackendCreationError) -> Self {
-        PyValueError::new_err(err.to_string())
+        Python::with_gil(|py| crate::exceptions::backend_error(py, err.to_string()))
     }
 }
 
 impl From<DistanceRegistryError> for PyErr {
     fn from(e: DistanceRegistryError) -> PyErr {
-        PyRuntimeError::new_err(e.to_string())
+        let message = e.to_string();
+        match e {
+            // Already the real exception the callback raised — restore it
+            // unchanged, under the GIL, instead of collapsing it to a string
+            // and wrapping it in a generic `RuntimeError`.
+            DistanceRegistryError::PythonCallFailed(err) => err,
+            DistanceRegistryError::MetricNotFound(_) => {
+                Python::with_gil(|py| crate::exceptions::metric_error(py, message))
+            }
+            _ => PyRuntimeError::new_err(message),
+        }
     }
 }
 
 impl From<RustAnnError> for PyErr {
     fn from(err: RustAnnError) -> Self {
-        PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(err.to_string())
+        err.into_pyerr()
     }
 }
 
@@ -105,3 +197,42 @@ impl<T> From<PoisonError<T>> for DistanceRegistryError {
         Self::LockPoisoned
     }
 }
+
+/// Opt-in `anyhow` integration (`--features anyhow`), for internal code that
+/// wants to use `anyhow::Result`/`.context(...)` through the index-building
+/// and IO layers while still surfacing clean exceptions at the pyo3
+/// boundary.
+#[cfg(feature = "anyhow")]
+mod anyhow_support {
+    use super::RustAnnError;
+    use pyo3::exceptions::PyRuntimeError;
+    use pyo3::PyErr;
+
+    impl From<anyhow::Error> for RustAnnError {
+        fn from(err: anyhow::Error) -> Self {
+            // If a `PyErr` propagated up through `.context(...)` calls, it's
+            // already the real exception to raise — unwrap and reuse it (via
+            // `Callback`, the variant `into_pyerr` hands back unchanged)
+            // rather than flattening it into a fresh `RuntimeError` and
+            // losing its class and traceback.
+            match err.downcast::<PyErr>() {
+                Ok(py_err) => RustAnnError::Callback(py_err),
+                Err(err) => RustAnnError::Other(format!("{err:?}"), Some(err.into())),
+            }
+        }
+    }
+
+    /// Convert an `anyhow::Error` straight to a `PyErr`, for call sites that
+    /// don't need the intermediate `RustAnnError`. Same downcast-the-chain
+    /// behavior as the `From` impl above: an inner `PyErr` is unwrapped and
+    /// re-raised unchanged rather than re-wrapped.
+    pub fn anyhow_to_pyerr(err: anyhow::Error) -> PyErr {
+        match err.downcast::<PyErr>() {
+            Ok(py_err) => py_err,
+            Err(err) => PyRuntimeError::new_err(format!("{err:?}")),
+        }
+    }
+}
+
+#[cfg(feature = "anyhow")]
+pub use anyhow_support::anyhow_to_pyerr;
@@ -0,0 +1,169 @@
+//! First-class `annie` exception hierarchy.
+//!
+//! Before this module, every failure surfaced through [`crate::errors`] was
+//! mapped onto a generic PyO3 built-in (`ValueError`, `RuntimeError`,
+//! `IOError`), so callers had no single base class to catch "any annie
+//! error" with and no way to write `except annie.DimensionError` for a
+//! specific failure mode. This module defines that hierarchy: a root
+//! `AnnError` (subclassing `Exception`), and named subclasses for the most
+//! common failure modes, each *also* subclassing the built-in its old
+//! generic exception used — so `except ValueError`/`except RuntimeError`/
+//! `except IOError` code written against the previous behavior keeps
+//! working for every `RustAnnError` variant. Two other conversions in
+//! [`crate::errors`] do change base class: an unsupported backend name
+//! (`BackendCreationError`) raises `BackendError`, an `IOError` subclass
+//! and not a `ValueError`, and an unknown registered metric
+//! (`DistanceRegistryError::MetricNotFound`) raises `MetricError`, a
+//! `ValueError` subclass and not a `RuntimeError`.
+//!
+//! PyO3's `create_exception!` macro only supports a single base class, so
+//! the multi-base types here are built the same way CPython itself builds
+//! them: via the `type(name, bases, namespace)` three-argument form of the
+//! builtin `type`, called once from [`init`] and cached in `GILOnceCell`s
+//! for the lifetime of the interpreter.
+//!
+//! `init`/`register` are meant to be called from the crate's `#[pymodule]`
+//! entry point (`m.add_wrapped`/`m.add` for classes and functions), which
+//! does not exist in this source tree. `register` is written the way it
+//! would be wired in once that entry point exists.
+
+use pyo3::exceptions::{PyException, PyIOError, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{PyDict, PyTuple, PyType};
+
+static ANN_ERROR: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+static DIMENSION_ERROR: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+static DUPLICATE_ID_ERROR: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+static EMPTY_INDEX_ERROR: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+static ALLOCATION_ERROR: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+static METRIC_ERROR: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+static BACKEND_ERROR: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+
+/// Build one exception type named `annie.{name}` with the given `bases` and
+/// `__doc__`, via the builtin `type(name, bases, namespace)`.
+fn build_exception_type(
+    py: Python<'_>,
+    name: &str,
+    bases: &PyTuple,
+    doc: &str,
+) -> PyResult<Py<PyType>> {
+    let namespace = PyDict::new(py);
+    namespace.set_item("__doc__", doc)?;
+    let type_fn = PyModule::import(py, "builtins")?.getattr("type")?;
+    let created = type_fn.call1((format!("annie.{name}"), bases, namespace))?;
+    Ok(created.downcast::<PyType>()?.into())
+}
+
+/// Build the `annie` exception hierarchy and cache it for the lifetime of
+/// the interpreter. Idempotent: a second call is a cheap no-op because each
+/// type is stashed behind a `GILOnceCell`.
+pub fn init(py: Python<'_>) -> PyResult<()> {
+    if ANN_ERROR.get(py).is_some() {
+        return Ok(());
+    }
+
+    let exception = py.get_type::<PyException>();
+    let ann_error = build_exception_type(
+        py,
+        "AnnError",
+        PyTuple::new(py, [exception]),
+        "Base class for all exceptions raised by annie.",
+    )?;
+    let ann_error_ref = ann_error.as_ref(py);
+
+    let value_error = py.get_type::<PyValueError>();
+    let runtime_error = py.get_type::<PyRuntimeError>();
+    let io_error = py.get_type::<PyIOError>();
+
+    let dimension_error = build_exception_type(
+        py,
+        "DimensionError",
+        PyTuple::new(py, [ann_error_ref, value_error]),
+        "Raised when a vector's dimensionality doesn't match the index's.",
+    )?;
+    let duplicate_id_error = build_exception_type(
+        py,
+        "DuplicateIdError",
+        PyTuple::new(py, [ann_error_ref, value_error]),
+        "Raised when an id being inserted already exists in the index.",
+    )?;
+    let empty_index_error = build_exception_type(
+        py,
+        "EmptyIndexError",
+        PyTuple::new(py, [ann_error_ref, value_error]),
+        "Raised when an operation requires a non-empty index.",
+    )?;
+    let allocation_error = build_exception_type(
+        py,
+        "AllocationError",
+        PyTuple::new(py, [ann_error_ref, runtime_error]),
+        "Raised when a memory allocation (host or GPU) fails.",
+    )?;
+    let metric_error = build_exception_type(
+        py,
+        "MetricError",
+        PyTuple::new(py, [ann_error_ref, value_error]),
+        "Raised when a distance metric is unknown or misconfigured.",
+    )?;
+    let backend_error = build_exception_type(
+        py,
+        "BackendError",
+        PyTuple::new(py, [ann_error_ref, io_error]),
+        "Raised for backend I/O and (de)serialization failures.",
+    )?;
+
+    ANN_ERROR.set(py, ann_error).ok();
+    DIMENSION_ERROR.set(py, dimension_error).ok();
+    DUPLICATE_ID_ERROR.set(py, duplicate_id_error).ok();
+    EMPTY_INDEX_ERROR.set(py, empty_index_error).ok();
+    ALLOCATION_ERROR.set(py, allocation_error).ok();
+    METRIC_ERROR.set(py, metric_error).ok();
+    BACKEND_ERROR.set(py, backend_error).ok();
+    Ok(())
+}
+
+/// Register the `annie` exception hierarchy onto the extension module, so
+/// `import annie; annie.DimensionError` works from Python. Call from the
+/// crate's `#[pymodule]` init function, after [`init`].
+pub fn register(py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    init(py)?;
+    m.add("AnnError", ANN_ERROR.get(py).expect("init() ran above").as_ref(py))?;
+    m.add("DimensionError", DIMENSION_ERROR.get(py).expect("init() ran above").as_ref(py))?;
+    m.add("DuplicateIdError", DUPLICATE_ID_ERROR.get(py).expect("init() ran above").as_ref(py))?;
+    m.add("EmptyIndexError", EMPTY_INDEX_ERROR.get(py).expect("init() ran above").as_ref(py))?;
+    m.add("AllocationError", ALLOCATION_ERROR.get(py).expect("init() ran above").as_ref(py))?;
+    m.add("MetricError", METRIC_ERROR.get(py).expect("init() ran above").as_ref(py))?;
+    m.add("BackendError", BACKEND_ERROR.get(py).expect("init() ran above").as_ref(py))?;
+    Ok(())
+}
+
+/// Build a `PyErr` of one of the types above from `msg`, initializing the
+/// hierarchy on first use so callers don't need to sequence their own call
+/// to [`init`] before the first error can be raised.
+fn new_err(py: Python<'_>, cell: &GILOnceCell<Py<PyType>>, msg: String) -> PyErr {
+    init(py).expect("annie exception hierarchy failed to initialize");
+    PyErr::from_type(cell.get(py).expect("init() ran above").as_ref(py), msg)
+}
+
+pub fn ann_error(py: Python<'_>, msg: impl Into<String>) -> PyErr {
+    new_err(py, &ANN_ERROR, msg.into())
+}
+pub fn dimension_error(py: Python<'_>, msg: impl Into<String>) -> PyErr {
+    new_err(py, &DIMENSION_ERROR, msg.into())
+}
+pub fn duplicate_id_error(py: Python<'_>, msg: impl Into<String>) -> PyErr {
+    new_err(py, &DUPLICATE_ID_ERROR, msg.into())
+}
+pub fn empty_index_error(py: Python<'_>, msg: impl Into<String>) -> PyErr {
+    new_err(py, &EMPTY_INDEX_ERROR, msg.into())
+}
+pub fn allocation_error(py: Python<'_>, msg: impl Into<String>) -> PyErr {
+    new_err(py, &ALLOCATION_ERROR, msg.into())
+}
+pub fn metric_error(py: Python<'_>, msg: impl Into<String>) -> PyErr {
+    new_err(py, &METRIC_ERROR, msg.into())
+}
+pub fn backend_error(py: Python<'_>, msg: impl Into<String>) -> PyErr {
+    new_err(py, &BACKEND_ERROR, msg.into())
+}
@@ -1,15 +1,95 @@
-use crate::gpu::{GpuBackend, GpuError, Precision};
+use crate::gpu::{GpuBackend, GpuError, Precision, QBLOCK_SIZE};
 use cust::prelude::*;
-use crate::gpu::memory::GpuMemoryPool;
+use cust::device::{Device, DeviceAttribute};
+use cust::event::{Event, EventFlags};
+use cust::stream::StreamWaitEventFlags;
+use cust::memory::AsyncCopyDestination;
+use crate::gpu::memory::{bucket_index, bucket_size, GpuMemoryPool};
 use lazy_static::lazy_static;
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use half::f16;
 use std::convert::TryInto;
 
 // Global memory pool with thread-safe access
 lazy_static! {
-    static ref MEMORY_POOL: Arc<Mutex<GpuMemoryPool>> = 
+    static ref MEMORY_POOL: Arc<Mutex<GpuMemoryPool>> =
         Arc::new(Mutex::new(GpuMemoryPool::new()));
+    static ref DEVICE_STAGING_POOL: Mutex<DeviceStagingPool> = Mutex::new(DeviceStagingPool::new());
+    /// Compiled PTX modules keyed by (device, precision), so the JIT/load
+    /// cost of `Module::from_ptx` is paid once per device/precision pair
+    /// rather than on every `l2_distance` call.
+    static ref MODULE_CACHE: Mutex<HashMap<(usize, Precision), Arc<Module>>> = Mutex::new(HashMap::new());
+    /// Compiled module for the precision-agnostic top-k reduction kernel
+    /// (it always operates on the f32 distance matrix regardless of what
+    /// precision computed it), loaded once and reused across `search` calls.
+    static ref TOPK_MODULE: Mutex<Option<Arc<Module>>> = Mutex::new(None);
+}
+
+/// Ring pool of reusable device-side allocations, keyed by device and
+/// rounded-up byte size, so repeated query/corpus/result transfers of
+/// similar size reuse the same device memory instead of allocating fresh
+/// `DeviceBuffer`s on every call.
+struct DeviceStagingPool {
+    free: HashMap<(usize, usize), Vec<DeviceBuffer<u8>>>,
+}
+
+impl DeviceStagingPool {
+    fn new() -> Self {
+        Self { free: HashMap::new() }
+    }
+
+    fn checkout(&mut self, device_id: usize, bytes: usize) -> Result<DeviceBuffer<u8>, GpuError> {
+        let capacity = bucket_size(bucket_index(bytes));
+        if let Some(bufs) = self.free.get_mut(&(device_id, capacity)) {
+            if let Some(buf) = bufs.pop() {
+                return Ok(buf);
+            }
+        }
+        DeviceBuffer::<u8>::zeroed(capacity).map_err(GpuError::Cuda)
+    }
+
+    fn checkin(&mut self, device_id: usize, buf: DeviceBuffer<u8>) {
+        self.free.entry((device_id, buf.len())).or_insert_with(Vec::new).push(buf);
+    }
+}
+
+/// RAII handle for a device buffer on loan from `DEVICE_STAGING_POOL`;
+/// only the host-requested prefix is meaningful, the rest is rounding slack
+/// that lets the same allocation serve future, similarly-sized requests.
+struct DeviceStagingBuffer {
+    buf: Option<DeviceBuffer<u8>>,
+    device_id: usize,
+    len: usize,
+}
+
+impl DeviceStagingBuffer {
+    fn upload(device_id: usize, host: &[u8]) -> Result<Self, GpuError> {
+        let mut buf = DEVICE_STAGING_POOL.lock().unwrap().checkout(device_id, host.len())?;
+        buf.index(0..host.len()).copy_from(host).map_err(GpuError::Cuda)?;
+        Ok(Self { buf: Some(buf), device_id, len: host.len() })
+    }
+
+    fn zeroed(device_id: usize, bytes: usize) -> Result<Self, GpuError> {
+        let buf = DEVICE_STAGING_POOL.lock().unwrap().checkout(device_id, bytes)?;
+        Ok(Self { buf: Some(buf), device_id, len: bytes })
+    }
+
+    fn slice(&self) -> cust::memory::DeviceSlice<u8> {
+        self.buf.as_ref().unwrap().index(0..self.len)
+    }
+
+    fn slice_mut(&mut self) -> cust::memory::DeviceSlice<u8> {
+        self.buf.as_mut().unwrap().index(0..self.len)
+    }
+}
+
+impl Drop for DeviceStagingBuffer {
+    fn drop(&mut self) {
+        if let Some(buf) = self.buf.take() {
+            DEVICE_STAGING_POOL.lock().unwrap().checkin(self.device_id, buf);
+        }
+    }
 }
 
 /// CUDA backend implementation
@@ -38,18 +118,29 @@ impl GpuBackend for CudaBackend {
         cust::device::set_device(device_id as u32).map_err(GpuError::Cuda)?;
         let _ctx = cust::quick_init().map_err(GpuError::Cuda)?;
         let stream = Stream::new(StreamFlags::NON_BLOCKING, None).map_err(GpuError::Cuda)?;
-        
-        // Get kernel
-        let (kernel_name, ptx) = get_kernel_and_ptx(precision);
-        let module = Module::from_ptx(ptx, &[]).map_err(GpuError::Cuda)?;
+
+        if !CudaBackend::supports_precision(device_id, precision)? {
+            return Err(GpuError::InvalidInput(format!(
+                "device {} does not support {:?} (insufficient compute capability)",
+                device_id, precision
+            )));
+        }
+
+        // Get kernel, reusing a cached compiled module for this (device, precision)
+        // pair instead of re-JIT-ing PTX on every call.
+        let (kernel_name, _ptx) = get_kernel_and_ptx(precision);
+        let module = get_or_load_module(device_id, precision)?;
         let func = module.get_function(&kernel_name).map_err(GpuError::Cuda)?;
-        
+
         // Convert data to target precision
-        let (queries_conv, corpus_conv) = convert_data(queries, corpus, precision)?;
+        let (queries_conv, corpus_conv) = convert_data(queries, corpus, dim, precision)?;
         
-        // Validate converted data sizes
-        let expected_query_size = n_queries * dim * precision.element_size();
-        let expected_corpus_size = n_vectors * dim * precision.element_size();
+        // Validate converted data sizes. Block-quantized precisions quantize
+        // each row independently (see `convert_data`), so the per-row byte
+        // size is multiplied by the row count rather than block-dividing the
+        // whole flattened buffer, which would let blocks span row boundaries.
+        let expected_query_size = n_queries * precision.buffer_bytes(dim);
+        let expected_corpus_size = n_vectors * precision.buffer_bytes(dim);
         if queries_conv.len() != expected_query_size || corpus_conv.len() != expected_corpus_size {
             return Err(GpuError::InvalidInput("Converted buffer size mismatch".to_string()));
         }
@@ -69,34 +160,40 @@ impl GpuBackend for CudaBackend {
         
         // Release the pool lock before GPU operations
         drop(managed_pool);
-        
-        // Allocate device buffers with RAII cleanup
-        let d_query = DeviceBuffer::from_slice(query_buffer.as_slice()).map_err(GpuError::Cuda)?;
-        let d_corpus = DeviceBuffer::from_slice(corpus_buffer.as_slice()).map_err(GpuError::Cuda)?;
-        let mut d_output = DeviceBuffer::<f32>::zeroed(n_queries * n_vectors).map_err(GpuError::Cuda)?;
-        
+
+        // Upload through the device staging pool: buffers are recycled by
+        // rounded byte size, so repeated calls with similar-sized batches
+        // skip device allocation entirely.
+        let d_query = DeviceStagingBuffer::upload(device_id, query_buffer.as_slice())?;
+        let d_corpus = DeviceStagingBuffer::upload(device_id, corpus_buffer.as_slice())?;
+        let mut d_output = DeviceStagingBuffer::zeroed(device_id, n_queries * n_vectors * 4)?;
+
         // Launch kernel
         let block_size = 256;
         let grid_size = ((n_queries * n_vectors + block_size - 1) / block_size) as u32;
-        
+
         unsafe {
             launch!(func<<<grid_size, block_size, 0, stream>>>(
-                d_query.as_device_ptr(),
-                d_corpus.as_device_ptr(),
-                d_output.as_device_ptr(),
+                d_query.slice().as_device_ptr(),
+                d_corpus.slice().as_device_ptr(),
+                d_output.slice_mut().as_device_ptr(),
                 n_queries as i32,
                 n_vectors as i32,
                 dim as i32
             )).map_err(GpuError::Cuda)?;
         }
-        
+
         // Wait for completion and copy results
         stream.synchronize().map_err(GpuError::Cuda)?;
-        
-        let mut results = vec![0.0f32; n_queries * n_vectors];
-        d_output.copy_to(&mut results).map_err(GpuError::Cuda)?;
-        
-        // Buffers automatically cleaned up by RAII Drop implementations
+
+        let mut result_bytes = vec![0u8; n_queries * n_vectors * 4];
+        d_output.slice().copy_to(&mut result_bytes).map_err(GpuError::Cuda)?;
+        let results: Vec<f32> = result_bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_ne_bytes(b.try_into().unwrap()))
+            .collect();
+
+        // Staging buffers are returned to the ring pool by RAII Drop.
         Ok(results)
     }
 // Refactor: Move device setup, kernel selection, memory management, and kernel launch into separate helper functions or modules to reduce complexity and improve maintainability.
@@ -109,26 +206,194 @@ impl GpuBackend for CudaBackend {
     fn device_count() -> usize {
         cust::device::get_count().unwrap_or(0) as usize
     }
+
+    fn search(
+        queries: &[f32],
+        corpus: &[f32],
+        dim: usize,
+        n_queries: usize,
+        n_vectors: usize,
+        k: usize,
+        device_id: usize,
+        precision: Precision,
+    ) -> Result<Vec<Vec<(usize, f32)>>, GpuError> {
+        if queries.is_empty() || corpus.is_empty() {
+            return Err(GpuError::InvalidInput("Empty input arrays".to_string()));
+        }
+        if queries.len() != n_queries * dim || corpus.len() != n_vectors * dim {
+            return Err(GpuError::InvalidInput("Input array sizes don't match specified dimensions".to_string()));
+        }
+        if k == 0 {
+            return Err(GpuError::InvalidInput("k must be greater than zero".to_string()));
+        }
+
+        cust::device::set_device(device_id as u32).map_err(GpuError::Cuda)?;
+        let _ctx = cust::quick_init().map_err(GpuError::Cuda)?;
+        let stream = Stream::new(StreamFlags::NON_BLOCKING, None).map_err(GpuError::Cuda)?;
+
+        if !CudaBackend::supports_precision(device_id, precision)? {
+            return Err(GpuError::InvalidInput(format!(
+                "device {} does not support {:?} (insufficient compute capability)",
+                device_id, precision
+            )));
+        }
+
+        let (kernel_name, _ptx) = get_kernel_and_ptx(precision);
+        let module = get_or_load_module(device_id, precision)?;
+        let func = module.get_function(&kernel_name).map_err(GpuError::Cuda)?;
+
+        let (queries_conv, corpus_conv) = convert_data(queries, corpus, dim, precision)?;
+        let expected_query_size = n_queries * precision.buffer_bytes(dim);
+        let expected_corpus_size = n_vectors * precision.buffer_bytes(dim);
+        if queries_conv.len() != expected_query_size || corpus_conv.len() != expected_corpus_size {
+            return Err(GpuError::InvalidInput("Converted buffer size mismatch".to_string()));
+        }
+
+        let pool = MEMORY_POOL.clone();
+        let mut managed_pool = pool.lock().unwrap();
+        let mut query_buffer = managed_pool.get_managed_buffer(device_id, queries_conv.len(), precision);
+        let mut corpus_buffer = managed_pool.get_managed_buffer(device_id, corpus_conv.len(), precision);
+        query_buffer.as_mut_slice().copy_from_slice(&queries_conv);
+        corpus_buffer.as_mut_slice().copy_from_slice(&corpus_conv);
+        drop(managed_pool);
+
+        let d_query = DeviceStagingBuffer::upload(device_id, query_buffer.as_slice())?;
+        let d_corpus = DeviceStagingBuffer::upload(device_id, corpus_buffer.as_slice())?;
+        // Unlike `l2_distance`, this full matrix never leaves the device:
+        // only the per-query top-k reduced below is ever copied back.
+        let mut d_distances = DeviceStagingBuffer::zeroed(device_id, n_queries * n_vectors * 4)?;
+
+        let block_size = 256;
+        let grid_size = ((n_queries * n_vectors + block_size - 1) / block_size) as u32;
+        unsafe {
+            launch!(func<<<grid_size, block_size, 0, stream>>>(
+                d_query.slice().as_device_ptr(),
+                d_corpus.slice().as_device_ptr(),
+                d_distances.slice_mut().as_device_ptr(),
+                n_queries as i32,
+                n_vectors as i32,
+                dim as i32
+            )).map_err(GpuError::Cuda)?;
+        }
+
+        // Second pass: one block per query scans that query's row of the
+        // still-device-resident distance matrix and keeps a bounded
+        // max-heap of the k smallest (distance, index) pairs, so only
+        // `n_queries * k` values ever cross the PCIe bus.
+        let topk_module = get_or_load_topk_module()?;
+        let topk_func = topk_module.get_function("topk_select_f32").map_err(GpuError::Cuda)?;
+
+        let mut d_topk_dist = DeviceStagingBuffer::zeroed(device_id, n_queries * k * 4)?;
+        let mut d_topk_idx = DeviceStagingBuffer::zeroed(device_id, n_queries * k * 4)?;
+
+        unsafe {
+            launch!(topk_func<<<n_queries as u32, 1, 0, stream>>>(
+                d_distances.slice().as_device_ptr(),
+                d_topk_dist.slice_mut().as_device_ptr(),
+                d_topk_idx.slice_mut().as_device_ptr(),
+                n_queries as i32,
+                n_vectors as i32,
+                k as i32
+            )).map_err(GpuError::Cuda)?;
+        }
+
+        stream.synchronize().map_err(GpuError::Cuda)?;
+
+        let mut dist_bytes = vec![0u8; n_queries * k * 4];
+        d_topk_dist.slice().copy_to(&mut dist_bytes).map_err(GpuError::Cuda)?;
+        let mut idx_bytes = vec![0u8; n_queries * k * 4];
+        d_topk_idx.slice().copy_to(&mut idx_bytes).map_err(GpuError::Cuda)?;
+
+        let dists: Vec<f32> = dist_bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_ne_bytes(b.try_into().unwrap()))
+            .collect();
+        let idxs: Vec<i32> = idx_bytes
+            .chunks_exact(4)
+            .map(|b| i32::from_ne_bytes(b.try_into().unwrap()))
+            .collect();
+
+        let k_eff = k.min(n_vectors);
+        let results = (0..n_queries)
+            .map(|q| {
+                (0..k_eff)
+                    .map(|i| (idxs[q * k + i] as usize, dists[q * k + i]))
+                    .collect()
+            })
+            .collect();
+
+        Ok(results)
+    }
 }
 
 /// Get kernel name and PTX based on precision
 fn get_kernel_and_ptx(precision: Precision) -> (String, &'static str) {
     match precision {
         Precision::Fp32 => ("l2_distance_fp32".to_string(), include_str!("kernels/l2_kernel_fp32.ptx")),
-        Precision::Fp16 => ("l2_distance_fp16".to_string(), include_str!("kernels/l2_kernel_fp16.ptx")), 
+        Precision::Fp16 => ("l2_distance_fp16".to_string(), include_str!("kernels/l2_kernel_fp16.ptx")),
         Precision::Int8 => ("l2_distance_int8".to_string(), include_str!("kernels/l2_kernel_int8.ptx")),
+        // Dequantize per block on the fly: accumulate (q_a * d_a) vs (q_b * d_b)
+        // for each matched pair of codes rather than materializing fp32 rows.
+        Precision::Q8_0 => ("l2_distance_q8_0".to_string(), include_str!("kernels/l2_kernel_q8_0.ptx")),
+        Precision::Q4_0 => ("l2_distance_q4_0".to_string(), include_str!("kernels/l2_kernel_q4_0.ptx")),
     }
 }
 
 impl CudaBackend {
-    /// Check if precision is supported
-    pub fn supports_precision(precision: Precision) -> bool {
-        match precision {
-            Precision::Fp32 | Precision::Fp16 | Precision::Int8 => true,
-        }
+    /// Check whether `device_id` can run the given precision's kernel,
+    /// based on its queried compute capability. fp16 arithmetic only
+    /// became worthwhile from Pascal (sm_60) onward, and the DP4A
+    /// instruction backing the int8/block-quantized dot-product kernels
+    /// requires sm_61 or newer.
+    pub fn supports_precision(device_id: usize, precision: Precision) -> Result<bool, GpuError> {
+        let (major, minor) = compute_capability(device_id)?;
+        Ok(match precision {
+            Precision::Fp32 => true,
+            Precision::Fp16 => (major, minor) >= (6, 0),
+            Precision::Int8 | Precision::Q8_0 | Precision::Q4_0 => (major, minor) >= (6, 1),
+        })
     }
 }
 
+/// Query a device's (major, minor) compute capability via `cust`'s device
+/// attributes.
+fn compute_capability(device_id: usize) -> Result<(i32, i32), GpuError> {
+    let device = Device::get_device(device_id as u32).map_err(GpuError::Cuda)?;
+    let major = device
+        .get_attribute(DeviceAttribute::ComputeCapabilityMajor)
+        .map_err(GpuError::Cuda)?;
+    let minor = device
+        .get_attribute(DeviceAttribute::ComputeCapabilityMinor)
+        .map_err(GpuError::Cuda)?;
+    Ok((major, minor))
+}
+
+/// Return the cached compiled module for `(device_id, precision)`, loading
+/// and JIT-compiling its PTX on first use.
+fn get_or_load_module(device_id: usize, precision: Precision) -> Result<Arc<Module>, GpuError> {
+    let mut cache = MODULE_CACHE.lock().unwrap();
+    if let Some(module) = cache.get(&(device_id, precision)) {
+        return Ok(module.clone());
+    }
+    let (_, ptx) = get_kernel_and_ptx(precision);
+    let module = Arc::new(Module::from_ptx(ptx, &[]).map_err(GpuError::Cuda)?);
+    cache.insert((device_id, precision), module.clone());
+    Ok(module)
+}
+
+/// Return the cached compiled module for the top-k reduction kernel,
+/// loading and JIT-compiling its PTX on first use.
+fn get_or_load_topk_module() -> Result<Arc<Module>, GpuError> {
+    let mut cache = TOPK_MODULE.lock().unwrap();
+    if let Some(module) = cache.as_ref() {
+        return Ok(module.clone());
+    }
+    let ptx = include_str!("kernels/topk_select_f32.ptx");
+    let module = Arc::new(Module::from_ptx(ptx, &[]).map_err(GpuError::Cuda)?);
+    *cache = Some(module.clone());
+    Ok(module)
+}
+
 /// Get device count for CUDA
 pub fn device_count() -> usize {
     cust::device::get_count().unwrap_or(0) as usize
@@ -136,6 +401,7 @@ pub fn device_count() -> usize {
 fn convert_data(
     queries: &[f32],
     corpus: &[f32],
+    dim: usize,
     precision: Precision,
 ) -> Result<(Vec<u8>, Vec<u8>), GpuError> {
     match precision {
@@ -176,32 +442,88 @@ fn convert_data(
             Ok((queries_bytes, corpus_bytes))
         }
         Precision::Int8 => {
-            // Convert to int8 with scaling
-            let queries_i8: Vec<i8> = queries.iter()
-                .map(|&x| (x * 127.0).clamp(-128.0, 127.0) as i8)
-                .collect();
-            let corpus_i8: Vec<i8> = corpus.iter()
-                .map(|&x| (x * 127.0).clamp(-128.0, 127.0) as i8)
-                .collect();
-            
-            // Reinterpret as bytes
-            let queries_bytes = unsafe {
-                std::slice::from_raw_parts(
-                    queries_i8.as_ptr() as *const u8,
-                    queries_i8.len()
-                ).to_vec()
-            };
-            let corpus_bytes = unsafe {
-                std::slice::from_raw_parts(
-                    corpus_i8.as_ptr() as *const u8,
-                    corpus_i8.len()
-                ).to_vec()
-            };
-            Ok((queries_bytes, corpus_bytes))
+            // Symmetric per-vector (per-row) quantization: each row gets its
+            // own scale `max(|x_i|) / 127` instead of the fixed `* 127.0`
+            // this used to apply uniformly, so a row's clamp range tracks
+            // its own magnitude rather than assuming every input already
+            // sits in `[-1, 1]`.
+            Ok((quantize_rows_int8(queries, dim), quantize_rows_int8(corpus, dim)))
+        }
+        Precision::Q8_0 => {
+            Ok((quantize_rows_q8_0(queries, dim), quantize_rows_q8_0(corpus, dim)))
+        }
+        Precision::Q4_0 => {
+            Ok((quantize_rows_q4_0(queries, dim), quantize_rows_q4_0(corpus, dim)))
         }
     }
 }
 
+/// Quantize each `dim`-element row of `data` independently into a single
+/// symmetric Int8 code: one little-endian fp16 scale `d = max(|x_i|)/127`
+/// per whole row (unlike `quantize_rows_q8_0`'s per-`QBLOCK_SIZE`-block
+/// scale), followed by `dim` signed bytes `round(x_i / d)`.
+fn quantize_rows_int8(data: &[f32], dim: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity((data.len() / dim.max(1)) * Precision::Int8.buffer_bytes(dim));
+    for row in data.chunks(dim) {
+        let amax = row.iter().fold(0.0f32, |m, &x| m.max(x.abs()));
+        let scale = if amax == 0.0 { 1.0 } else { amax / 127.0 };
+        out.extend_from_slice(&f16::from_f32(scale).to_le_bytes());
+        for &x in row {
+            out.push(((x / scale).round().clamp(-127.0, 127.0) as i8) as u8);
+        }
+    }
+    out
+}
+
+/// Quantize each `dim`-element row of `data` independently into GGML
+/// Q8_0-style blocks: per `QBLOCK_SIZE`-element block, one little-endian
+/// fp16 scale `d = max(|x_i|)/127` followed by `QBLOCK_SIZE` int8 codes
+/// `round(x_i / d)`. The final partial block of a row (if `dim` isn't a
+/// multiple of `QBLOCK_SIZE`) is zero-padded so every block is fixed-size.
+fn quantize_rows_q8_0(data: &[f32], dim: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity((data.len() / dim.max(1)) * Precision::Q8_0.buffer_bytes(dim));
+    for row in data.chunks(dim) {
+        for block in row.chunks(QBLOCK_SIZE) {
+            let amax = block.iter().fold(0.0f32, |m, &x| m.max(x.abs()));
+            let d = if amax == 0.0 { 1.0 } else { amax / 127.0 };
+            out.extend_from_slice(&f16::from_f32(d).to_le_bytes());
+            for &x in block {
+                out.push(((x / d).round().clamp(-127.0, 127.0) as i8) as u8);
+            }
+            for _ in block.len()..QBLOCK_SIZE {
+                out.push(0);
+            }
+        }
+    }
+    out
+}
+
+/// Quantize each `dim`-element row of `data` independently into GGML
+/// Q4_0-style blocks: per `QBLOCK_SIZE`-element block, one little-endian
+/// fp16 scale `d = max(|x_i|)/7` followed by `QBLOCK_SIZE / 2` bytes, each
+/// packing two signed 4-bit codes (offset by 8 so they fit unsigned
+/// nibbles) `round(x_i / d)`.
+fn quantize_rows_q4_0(data: &[f32], dim: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity((data.len() / dim.max(1)) * Precision::Q4_0.buffer_bytes(dim));
+    for row in data.chunks(dim) {
+        for block in row.chunks(QBLOCK_SIZE) {
+            let amax = block.iter().fold(0.0f32, |m, &x| m.max(x.abs()));
+            let d = if amax == 0.0 { 1.0 } else { amax / 7.0 };
+            out.extend_from_slice(&f16::from_f32(d).to_le_bytes());
+
+            let mut codes = [0u8; QBLOCK_SIZE];
+            for (i, &x) in block.iter().enumerate() {
+                let q = (x / d).round().clamp(-8.0, 7.0) as i32;
+                codes[i] = (q + 8) as u8;
+            }
+            for pair in codes.chunks(2) {
+                out.push(pair[0] | (pair[1] << 4));
+            }
+        }
+    }
+    out
+}
+
 /// Initialize CUDA context for a device
 pub fn init_device(device_id: usize) -> Result<(), GpuError> {
     cust::device::set_device(device_id as u32).map_err(GpuError::Cuda)?;
@@ -242,7 +564,149 @@ pub fn distribute_data(
     Ok(distributed)
 }
 
-/// Multi-GPU search
+/// Number of corpus vectors processed per pipeline stage sub-chunk. Smaller
+/// sub-chunks overlap copy and compute more finely but add per-launch
+/// overhead; this is a reasonable middle ground for typical vector sizes.
+const PIPELINE_CHUNK_VECTORS: usize = 4096;
+
+/// Persistent per-device CUDA context plus a pair of non-blocking streams:
+/// one for H2D corpus-chunk copies, one for kernel launches. Kept alive for
+/// the whole `multi_gpu_search` call so the pipeline stages below reuse a
+/// single context/stream pair per device instead of paying init cost (and
+/// losing async overlap) on every sub-chunk.
+struct DeviceContext {
+    device_id: usize,
+    _ctx: Context,
+    copy_stream: Stream,
+    compute_stream: Stream,
+}
+
+impl DeviceContext {
+    fn new(device_id: usize) -> Result<Self, GpuError> {
+        cust::device::set_device(device_id as u32).map_err(GpuError::Cuda)?;
+        let ctx = cust::quick_init().map_err(GpuError::Cuda)?;
+        let copy_stream = Stream::new(StreamFlags::NON_BLOCKING, None).map_err(GpuError::Cuda)?;
+        let compute_stream = Stream::new(StreamFlags::NON_BLOCKING, None).map_err(GpuError::Cuda)?;
+        Ok(Self { device_id, _ctx: ctx, copy_stream, compute_stream })
+    }
+}
+
+/// Run one device's shard of the search as a double-buffered software
+/// pipeline: while the kernel computes distances for sub-chunk N (on
+/// `compute_stream`), the H2D copy for sub-chunk N+1 is already in flight
+/// (on `copy_stream`), so copy and compute overlap instead of serializing.
+/// `compute_stream` only waits on a per-buffer `Event` recorded by
+/// `copy_stream`, and the whole device is synchronized exactly once, after
+/// every sub-chunk has been launched. Returns this device's local top-`k` as
+/// `(global corpus index, distance)` pairs.
+fn device_pipeline_search(
+    ctx: &DeviceContext,
+    query_bytes: &[u8],
+    corpus_bytes: &[u8],
+    dim: usize,
+    k: usize,
+    precision: Precision,
+    index_offset: usize,
+) -> Result<Vec<(usize, f32)>, GpuError> {
+    if !CudaBackend::supports_precision(ctx.device_id, precision)? {
+        return Err(GpuError::InvalidInput(format!(
+            "device {} does not support {:?} (insufficient compute capability)",
+            ctx.device_id, precision
+        )));
+    }
+
+    let row_bytes = precision.buffer_bytes(dim);
+    let n_vectors = corpus_bytes.len() / row_bytes.max(1);
+    if n_vectors == 0 {
+        return Ok(Vec::new());
+    }
+
+    let (kernel_name, _) = get_kernel_and_ptx(precision);
+    let module = get_or_load_module(ctx.device_id, precision)?;
+    let func = module.get_function(&kernel_name).map_err(GpuError::Cuda)?;
+
+    // Query is small and reused by every sub-chunk's kernel launch, so it's
+    // uploaded once up front rather than double-buffered.
+    let d_query = DeviceBuffer::from_slice(query_bytes).map_err(GpuError::Cuda)?;
+
+    let chunk_vectors = PIPELINE_CHUNK_VECTORS.min(n_vectors).max(1);
+    let n_chunks = (n_vectors + chunk_vectors - 1) / chunk_vectors;
+    let sub_chunk_len = |i: usize| (n_vectors - i * chunk_vectors).min(chunk_vectors);
+
+    let mut corpus_buffers = [
+        unsafe { DeviceBuffer::<u8>::uninitialized(chunk_vectors * row_bytes).map_err(GpuError::Cuda)? },
+        unsafe { DeviceBuffer::<u8>::uninitialized(chunk_vectors * row_bytes).map_err(GpuError::Cuda)? },
+    ];
+    let copy_done = [
+        Event::new(EventFlags::DEFAULT).map_err(GpuError::Cuda)?,
+        Event::new(EventFlags::DEFAULT).map_err(GpuError::Cuda)?,
+    ];
+    // One result buffer sized for the whole shard, so the kernel for each
+    // sub-chunk writes straight into its slice and a single D2H copy at the
+    // very end retrieves all of them.
+    let mut d_output = unsafe { DeviceBuffer::<f32>::uninitialized(n_vectors).map_err(GpuError::Cuda)? };
+
+    let copy_in = |slot: usize, chunk_idx: usize| -> Result<(), GpuError> {
+        let start = chunk_idx * chunk_vectors;
+        let len = sub_chunk_len(chunk_idx);
+        corpus_buffers[slot]
+            .index(0..len * row_bytes)
+            .async_copy_from(&corpus_bytes[start * row_bytes..(start + len) * row_bytes], &ctx.copy_stream)
+            .map_err(GpuError::Cuda)?;
+        copy_done[slot].record(&ctx.copy_stream).map_err(GpuError::Cuda)?;
+        Ok(())
+    };
+
+    // Prime the pipeline: kick off sub-chunk 0's H2D copy before entering the loop.
+    copy_in(0, 0)?;
+
+    let block_size = 256;
+    for i in 0..n_chunks {
+        let cur = i % 2;
+        let len = sub_chunk_len(i);
+        let start = i * chunk_vectors;
+
+        // Launch sub-chunk i+1's copy now so it overlaps this iteration's kernel.
+        if i + 1 < n_chunks {
+            copy_in((i + 1) % 2, i + 1)?;
+        }
+
+        // Compute only proceeds once this sub-chunk's copy has landed.
+        ctx.compute_stream.wait_event(&copy_done[cur], StreamWaitEventFlags::DEFAULT).map_err(GpuError::Cuda)?;
+
+        let grid_size = ((len + block_size - 1) / block_size) as u32;
+        unsafe {
+            launch!(func<<<grid_size, block_size, 0, ctx.compute_stream>>>(
+                d_query.as_device_ptr(),
+                corpus_buffers[cur].index(0..len * row_bytes).as_device_ptr(),
+                d_output.index(start..start + len).as_device_ptr(),
+                1i32,
+                len as i32,
+                dim as i32
+            )).map_err(GpuError::Cuda)?;
+        }
+    }
+
+    // Synchronize once, after every sub-chunk's copy and kernel have been enqueued.
+    ctx.compute_stream.synchronize().map_err(GpuError::Cuda)?;
+
+    let mut distances = vec![0.0f32; n_vectors];
+    d_output.copy_to(&mut distances).map_err(GpuError::Cuda)?;
+
+    let mut local_top_k: Vec<(usize, f32)> = distances
+        .into_iter()
+        .enumerate()
+        .map(|(j, d)| (index_offset + j, d))
+        .collect();
+    local_top_k.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+    local_top_k.truncate(k);
+    Ok(local_top_k)
+}
+
+/// Brute-force top-k search across every device in `data_chunks` (as
+/// produced by `distribute_data`). Each device's shard runs its own
+/// double-buffered copy/compute pipeline via `device_pipeline_search`, and
+/// the per-device top-k lists are merged into a single global top-k.
 pub fn multi_gpu_search(
     query: &[f32],
     data_chunks: &[(usize, Vec<u8>)],
@@ -250,60 +714,36 @@ pub fn multi_gpu_search(
     k: usize,
     precision: Precision,
 ) -> Result<Vec<(usize, f32)>, GpuError> {
-    let mut all_results = Vec::new();
-    let mut streams = Vec::new();
-    
-    // Create a stream per device
-    for (device_id, _) in data_chunks {
-        cust::device::set_device(*device_id as u32)?;
-        streams.push(Stream::new(StreamFlags::NON_BLOCKING, None)?);
-    }
-    
-    // Launch searches in parallel
-    let mut futures = Vec::new();
-    for ((device_id, data), stream) in data_chunks.iter().zip(streams.iter()) {
-        cust::device::set_device(*device_id as u32)?;
-        
-        // Convert query to target precision
-        let query_conv = match precision {
-            Precision::Fp32 => query.to_vec(),
-            Precision::Fp16 => query.iter().map(|&x| f16::from_f32(x).to_f32()).collect(),
-            Precision::Int8 => query.iter().map(|&x| (x * 127.0) as f32).collect(),
-        };
-        
-        let n_vectors = data.len() / (dim * precision.element_size());
-        let future = CudaBackend::l2_distance(
-            &query_conv,
-            &[], // Pass empty slice; l2_distance expects raw bytes for non-f32, so this call must be refactored
-            dim,
-            1,
-            n_vectors,
-            *device_id,
-            precision,
-        );
-        
-        futures.push(future);
+    if data_chunks.is_empty() {
+        return Ok(Vec::new());
     }
-    
-    // Collect results
-    for (i, future) in futures.into_iter().enumerate() {
-        let (device_id, _) = &data_chunks[i];
-        cust::device::set_device(*device_id as u32)?;
-        
-        let mut distances = future?;
-        let start_idx = i * (data_chunks[0].1.len() / (dim * precision.element_size()));
-        
-        all_results.extend(
-            distances.into_iter()
-                .enumerate()
-                .map(|(j, d)| (start_idx + j, d))
-        );
+
+    let query_bytes = {
+        let (queries_conv, _) = convert_data(query, query, dim, precision)?;
+        queries_conv
+    };
+
+    let mut index_offset = 0usize;
+    let mut all_results = Vec::new();
+    for (device_id, chunk_bytes) in data_chunks {
+        // `distribute_data` hands out raw native-endian f32 rows; encode them
+        // at `precision` before they reach the device pipeline.
+        let chunk_f32: Vec<f32> = chunk_bytes
+            .chunks_exact(std::mem::size_of::<f32>())
+            .map(|b| f32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        let n_vectors = chunk_f32.len() / dim.max(1);
+        let (_, corpus_conv) = convert_data(&[], &chunk_f32, dim, precision)?;
+
+        let ctx = DeviceContext::new(*device_id)?;
+        let shard_results =
+            device_pipeline_search(&ctx, &query_bytes, &corpus_conv, dim, k, precision, index_offset)?;
+        all_results.extend(shard_results);
+        index_offset += n_vectors;
     }
-    
-    // Merge results and select top-k
+
     all_results.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
     all_results.truncate(k);
-    
     Ok(all_results)
 }
 
@@ -327,6 +767,13 @@ pub fn get_memory_stats(device_id: usize) -> Result<GpuMemoryStats, GpuError> {
     })
 }
 
+/// Handle to the global CUDA memory pool, for callers (e.g. the Python
+/// `gpu_memory_report` binding) that need to pull a device's allocation
+/// event log rather than just the one-shot stats `memory_usage` exposes.
+pub fn memory_pool() -> Arc<Mutex<GpuMemoryPool>> {
+    MEMORY_POOL.clone()
+}
+
 /// Kernel warmup to reduce first-run latency
 pub fn warmup_kernels(device_id: usize) -> Result<(), GpuError> {
     cust::device::set_device(device_id as u32)?;
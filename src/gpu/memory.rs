@@ -1,6 +1,9 @@
 use crate::gpu::{GpuError, Precision};
+use std::cell::UnsafeCell;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 /// RAII wrapper for GPU memory buffer with automatic cleanup
 pub struct GpuBuffer {
@@ -89,6 +92,20 @@ impl GpuBufferBatch {
         self.buffers.push(gpu_buffer);
     }
 
+    /// Add a buffer to the batch after charging its size against `reservation`,
+    /// failing fast with `OutOfPoolMemory` instead of over-allocating.
+    pub fn try_add_buffer(
+        &mut self,
+        reservation: &mut MemoryReservation,
+        buffer: Vec<u8>,
+        size: usize,
+        precision: Precision,
+    ) -> Result<(), GpuError> {
+        reservation.try_grow(precision.buffer_bytes(size))?;
+        self.add_buffer(buffer, size, precision);
+        Ok(())
+    }
+
     /// Get a reference to a buffer by index
     pub fn get_buffer(&self, index: usize) -> Option<&GpuBuffer> {
         self.buffers.get(index)
@@ -123,130 +140,570 @@ impl Drop for GpuBufferBatch {
     }
 }
 
+/// Smallest bucket size (in bytes) tracked by the segregated free lists.
+const MIN_BUCKET_BYTES: usize = 256;
+/// Maximum number of cached blocks retained per bucket before cleanup trims it.
+const MAX_BLOCKS_PER_BUCKET: usize = 8;
+/// Default backing block size, as `2^DEFAULT_BLOCK_SIZE_LOG2` bytes (64 MiB),
+/// carved into uniform slots by the segregated-list sub-allocator. See
+/// [`Block`].
+pub const DEFAULT_BLOCK_SIZE_LOG2: u32 = 26;
+
+/// Compute the bucket index for a requested byte size: `ceil(log2(max(bytes, MIN_BUCKET_BYTES)))`.
+pub(crate) fn bucket_index(bytes: usize) -> u32 {
+    let bytes = bytes.max(MIN_BUCKET_BYTES);
+    let bits = usize::BITS - (bytes - 1).leading_zeros();
+    bits.max(MIN_BUCKET_BYTES.trailing_zeros())
+}
+
+/// Byte size of the blocks stored in a given bucket.
+pub(crate) fn bucket_size(bucket: u32) -> usize {
+    1usize << bucket
+}
+
+/// Maximum number of `AllocationEvent`s retained per device; older events
+/// are dropped once the log fills up rather than growing it unbounded.
+const MAX_EVENTS_PER_DEVICE: usize = 1024;
+
+thread_local! {
+    /// Stack of operation names pushed by `ScopedOperation::enter`, so
+    /// allocations made while one is active get attributed to it without
+    /// threading a name parameter through every buffer-request call site.
+    static OPERATION_STACK: std::cell::RefCell<Vec<String>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+fn current_operation() -> String {
+    OPERATION_STACK.with(|stack| stack.borrow().last().cloned().unwrap_or_else(|| "unlabeled".to_string()))
+}
+
+/// RAII guard that attributes any buffer allocations/returns made on this
+/// thread while it's alive to `operation`, so `memory_report`'s event log
+/// can be traced back to the search call that caused them instead of just
+/// a device/precision/size tuple.
+pub struct ScopedOperation;
+
+impl ScopedOperation {
+    pub fn enter(operation: impl Into<String>) -> Self {
+        OPERATION_STACK.with(|stack| stack.borrow_mut().push(operation.into()));
+        Self
+    }
+}
+
+impl Drop for ScopedOperation {
+    fn drop(&mut self) {
+        OPERATION_STACK.with(|stack| {
+            stack.borrow_mut().pop();
+        });
+    }
+}
+
+/// Whether an `AllocationEvent` was a checkout from the pool (and whether it
+/// was served from the free list) or a return back into it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllocationEventKind {
+    Allocate { cache_hit: bool },
+    Deallocate,
+}
+
+/// One recorded checkout or return against a device's pool: what it was for,
+/// how big, and when, so `memory_report` can show *why* a device's usage
+/// grew instead of just the aggregate counters in `MemoryStats`.
+#[derive(Debug, Clone)]
+pub struct AllocationEvent {
+    pub timestamp: Instant,
+    pub size_bytes: usize,
+    pub precision: Precision,
+    pub operation: String,
+    pub kind: AllocationEventKind,
+}
+
+/// Lock-free allocation counters for one device's pool. Held in an `Arc`
+/// shared between the pool's `Mutex<DeviceMemoryPool>` (which only mutates
+/// the free lists) and callers that just want to read stats, so a monitor
+/// polling cache efficiency never contends with allocation traffic.
+#[derive(Default)]
+struct DeviceStats {
+    allocated: AtomicUsize,
+    peak_usage: AtomicUsize,
+    allocation_count: AtomicU64,
+    deallocation_count: AtomicU64,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+    /// Sum of `bucket_size - requested_size` over outstanding allocations:
+    /// bytes reserved by the segregated-list allocator's power-of-two
+    /// rounding that no caller actually asked for.
+    fragmentation_bytes: AtomicUsize,
+    /// Guards only the timestamp itself; cleanup is rare enough that this
+    /// doesn't reintroduce contention on the hot allocate/free path.
+    last_cleanup: Mutex<Option<Instant>>,
+    /// Bounded ring of recent allocation/deallocation events (see
+    /// `AllocationEvent`), for diagnosing OOMs and fragmentation instead of
+    /// guessing from the aggregate counters alone.
+    events: Mutex<std::collections::VecDeque<AllocationEvent>>,
+}
+
+impl DeviceStats {
+    fn record_event(&self, size_bytes: usize, precision: Precision, kind: AllocationEventKind) {
+        let mut events = self.events.lock().unwrap();
+        if events.len() >= MAX_EVENTS_PER_DEVICE {
+            events.pop_front();
+        }
+        events.push_back(AllocationEvent {
+            timestamp: Instant::now(),
+            size_bytes,
+            precision,
+            operation: current_operation(),
+            kind,
+        });
+    }
+
+    fn record_allocation(&self, bytes: usize, bucket_bytes: usize, precision: Precision, cache_hit: bool) {
+        let allocated = self.allocated.fetch_add(bytes, Ordering::AcqRel) + bytes;
+        self.peak_usage.fetch_max(allocated, Ordering::AcqRel);
+        self.allocation_count.fetch_add(1, Ordering::Relaxed);
+        self.fragmentation_bytes.fetch_add(bucket_bytes.saturating_sub(bytes), Ordering::AcqRel);
+        if cache_hit {
+            self.cache_hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.cache_misses.fetch_add(1, Ordering::Relaxed);
+        }
+        self.record_event(bytes, precision, AllocationEventKind::Allocate { cache_hit });
+    }
+
+    fn record_deallocation(&self, bytes: usize, bucket_bytes: usize, precision: Precision) {
+        self.allocated
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |cur| Some(cur.saturating_sub(bytes)))
+            .ok();
+        self.fragmentation_bytes
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |cur| {
+                Some(cur.saturating_sub(bucket_bytes.saturating_sub(bytes)))
+            })
+            .ok();
+        self.deallocation_count.fetch_add(1, Ordering::Relaxed);
+        self.record_event(bytes, precision, AllocationEventKind::Deallocate);
+    }
+
+    fn snapshot(&self) -> MemoryStats {
+        MemoryStats {
+            allocated: self.allocated.load(Ordering::Acquire),
+            peak_usage: self.peak_usage.load(Ordering::Acquire),
+            allocation_count: self.allocation_count.load(Ordering::Relaxed),
+            deallocation_count: self.deallocation_count.load(Ordering::Relaxed),
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
+            cache_misses: self.cache_misses.load(Ordering::Relaxed),
+            fragmentation_bytes: self.fragmentation_bytes.load(Ordering::Acquire),
+            last_cleanup: *self.last_cleanup.lock().unwrap(),
+        }
+    }
+}
+
+/// Per-device memory report: current aggregate stats plus the bounded
+/// recent allocation/deallocation event log, for diagnosing OOMs and
+/// fragmentation in a `GpuMemoryPool` instead of guessing from the
+/// aggregate counters alone.
+#[derive(Debug, Clone)]
+pub struct DeviceMemoryReport {
+    pub device_id: usize,
+    pub stats: MemoryStats,
+    pub events: Vec<AllocationEvent>,
+}
+
+/// Point-in-time snapshot of a device pool's allocation statistics, read
+/// without taking the pool's allocation mutex.
+#[derive(Debug, Clone, Copy)]
+pub struct MemoryStats {
+    pub allocated: usize,
+    pub peak_usage: usize,
+    pub allocation_count: u64,
+    pub deallocation_count: u64,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    /// Bytes reserved by power-of-two bucket rounding beyond what callers
+    /// actually requested, summed over outstanding allocations.
+    pub fragmentation_bytes: usize,
+    pub last_cleanup: Option<Instant>,
+}
+
+impl MemoryStats {
+    /// Fraction of buffer requests satisfied from the free list rather than
+    /// freshly allocated, in `[0.0, 1.0]`.
+    pub fn cache_efficiency(&self) -> f32 {
+        let total = self.cache_hits + self.cache_misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.cache_hits as f32 / total as f32
+        }
+    }
+}
+
+/// One large backing block, pre-divided into uniform `slot_bytes` slots, that
+/// `DeviceMemoryPool::get_buffer` carves sub-allocations out of instead of
+/// allocating a fresh `Vec<u8>` per request. Slots within a block are
+/// fungible — they're all the same size — so a returned buffer can go back
+/// into any slot of the block it's released to; once every slot in a block
+/// is free, `DeviceMemoryPool` can drop the whole block in one step rather
+/// than tracking its fragments individually.
+struct Block {
+    slot_bytes: usize,
+    capacity: usize,
+    /// `(offset, buffer, last_used)` for every slot currently sitting idle.
+    /// Its length reaching `capacity` means the whole block is free.
+    /// `last_used` is the `return_slot` time, so `evict_lru` can reclaim the
+    /// coldest slots first instead of dumping the whole cache.
+    free: Vec<(usize, Vec<u8>, Instant)>,
+}
+
+impl Block {
+    /// Carve a fresh `2^block_size_log2`-byte block into `slot_bytes`-sized
+    /// slots, materializing each slot's backing storage up front.
+    fn new(slot_bytes: usize, block_size_log2: u32) -> Self {
+        let block_bytes = 1usize << block_size_log2;
+        let capacity = (block_bytes / slot_bytes).max(1);
+        let now = Instant::now();
+        let free = (0..capacity).map(|i| (i * slot_bytes, vec![0u8; slot_bytes], now)).collect();
+        Self { slot_bytes, capacity, free }
+    }
+
+    fn is_fully_free(&self) -> bool {
+        self.free.len() == self.capacity
+    }
+
+    /// Total bytes this block holds resident, whether idle or checked out.
+    fn resident_bytes(&self) -> usize {
+        self.capacity * self.slot_bytes
+    }
+
+    fn take_free_slot(&mut self) -> Option<(usize, Vec<u8>)> {
+        self.free.pop().map(|(offset, buffer, _)| (offset, buffer))
+    }
+
+    /// Hand a slot's buffer back to the block. Any free offset works since
+    /// every slot in a block is the same size; the offset recorded here is
+    /// only for bookkeeping symmetry with `take_free_slot`, not identity.
+    fn return_slot(&mut self, buffer: Vec<u8>) {
+        let offset = self.free.len() * self.slot_bytes;
+        self.free.push((offset, buffer, Instant::now()));
+    }
+
+    /// Index of this block's least-recently-returned free slot, if any.
+    fn lru_free_slot(&self) -> Option<usize> {
+        self.free
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, (_, _, last_used))| *last_used)
+            .map(|(idx, _)| idx)
+    }
+
+    /// Permanently drop the free slot at `idx`, shrinking this block's
+    /// capacity by one. Used by `evict_lru`, which only ever targets idle
+    /// slots — a slot currently checked out isn't in `free` to begin with.
+    fn evict_slot(&mut self, idx: usize) -> usize {
+        let (_, buffer, _) = self.free.remove(idx);
+        self.capacity -= 1;
+        buffer.len()
+    }
+}
+
 struct DeviceMemoryPool {
-    buffers: HashMap<(usize, Precision), Vec<Vec<u8>>>,
-    allocated: usize,
-    peak_usage: usize,
+    /// Segregated blocks keyed by (bucket index, precision). Each block is
+    /// `2^block_size_log2` bytes carved into uniform `bucket_size(bucket)`
+    /// slots (see [`Block`]); whole blocks are released once every slot in
+    /// them frees, instead of tracking individual buffers forever.
+    blocks: HashMap<(u32, Precision), Vec<Block>>,
     max_pool_size: usize,
     fragmentation_threshold: f32,
+    /// Size of each backing block, as `2^block_size_log2` bytes. Configurable
+    /// via `set_block_size_log2`; defaults to [`DEFAULT_BLOCK_SIZE_LOG2`] (64 MiB).
+    block_size_log2: u32,
+    /// Pressure ratio (see `memory_pressure`) above which `get_buffer` runs
+    /// `evict_lru` to reclaim cold cached slots before treating the pool as
+    /// full. Configurable via `set_watermarks`; defaults to 0.85.
+    high_watermark: f32,
+    /// Pressure ratio `evict_lru` stops at, so a momentary spike above
+    /// `high_watermark` doesn't evict every cached slot in one pass.
+    /// Configurable via `set_watermarks`; defaults to 0.6.
+    low_watermark: f32,
+    /// Bytes claimed by outstanding `MemoryReservation`s but not necessarily
+    /// allocated yet; counted against `max_pool_size` alongside `allocated`.
+    reserved: AtomicUsize,
+    /// Lock-free counters shared with `GpuMemoryPool` so stats can be read
+    /// without locking this pool.
+    stats: Arc<DeviceStats>,
 }
 
 impl DeviceMemoryPool {
-    fn new() -> Self {
+    fn new(stats: Arc<DeviceStats>) -> Self {
         Self {
-            buffers: HashMap::new(),
-            allocated: 0,
-            peak_usage: 0,
+            blocks: HashMap::new(),
             max_pool_size: 1024 * 1024 * 1024, // 1GB default max pool size
             fragmentation_threshold: 0.5, // Cleanup when 50% fragmented
+            block_size_log2: DEFAULT_BLOCK_SIZE_LOG2,
+            high_watermark: 0.85,
+            low_watermark: 0.6,
+            reserved: AtomicUsize::new(0),
+            stats,
+        }
+    }
+
+    fn allocated(&self) -> usize {
+        self.stats.allocated.load(Ordering::Acquire)
+    }
+
+    fn set_block_size_log2(&mut self, log2: u32) {
+        self.block_size_log2 = log2;
+    }
+
+    /// Claim `bytes` against the pool's hard limit without allocating yet.
+    /// Fails fast with `OutOfPoolMemory` instead of letting the pool grow
+    /// past `max_pool_size`.
+    fn try_reserve(&mut self, bytes: usize) -> Result<(), GpuError> {
+        let in_use = self.allocated() + self.reserved.load(Ordering::Acquire);
+        if in_use + bytes > self.max_pool_size {
+            return Err(GpuError::OutOfPoolMemory {
+                requested: bytes,
+                available: self.max_pool_size.saturating_sub(in_use),
+            });
         }
+        self.reserved.fetch_add(bytes, Ordering::AcqRel);
+        Ok(())
+    }
+
+    /// Release a previously reserved byte count.
+    fn release_reservation(&mut self, bytes: usize) {
+        self.reserved.fetch_update(Ordering::AcqRel, Ordering::Acquire, |cur| Some(cur.saturating_sub(bytes))).ok();
     }
 
     fn get_buffer(&mut self, size: usize, precision: Precision) -> Vec<u8> {
-        let key = (size, precision);
-        if let Some(buffers) = self.buffers.get_mut(&key) {
-            if let Some(buf) = buffers.pop() {
-                self.record_allocation(buf.len());
-                return buf;
+        if self.memory_pressure() > self.high_watermark {
+            self.evict_lru();
+        }
+
+        let bytes = precision.buffer_bytes(size);
+        let bucket = bucket_index(bytes);
+        let bucket_bytes = bucket_size(bucket);
+        let key = (bucket, precision);
+
+        if let Some(blocks) = self.blocks.get_mut(&key) {
+            for block in blocks.iter_mut() {
+                if let Some((_, mut buf)) = block.take_free_slot() {
+                    debug_assert_eq!(buf.len(), bucket_bytes);
+                    buf.truncate(bytes);
+                    self.stats.record_allocation(bytes, bucket_bytes, precision, true);
+                    return buf;
+                }
             }
         }
-        
-        let elem_size = match precision {
-            Precision::Fp32 => 4,
-            Precision::Fp16 => 2,
-            Precision::Int8 => 1,
-        };
-        let bytes = size * elem_size;
-        
-        // Check memory pressure before allocation
-        if self.allocated + bytes > self.max_pool_size {
+
+        // No free slot in any existing block for this bucket: check memory
+        // pressure, then carve a fresh block into same-size slots.
+        if self.allocated() + bucket_bytes > self.max_pool_size {
             self.cleanup_fragmented_buffers();
         }
-        
-        self.record_allocation(bytes);
-        vec![0u8; bytes]
-    }
 
-    fn return_buffer(&mut self, buffer: Vec<u8>, size: usize, precision: Precision) {
-        let key = (size, precision);
-        let buffer_size = buffer.len();
-        
-        // Check if we should keep this buffer or drop it due to memory pressure
-        if self.allocated < self.max_pool_size {
-            self.buffers.entry(key).or_insert_with(Vec::new).push(buffer);
-        }
-        
-        self.record_deallocation(buffer_size);
+        let mut block = Block::new(bucket_bytes, self.block_size_log2);
+        let (_, mut buf) = block
+            .take_free_slot()
+            .expect("a freshly carved block always has at least one slot");
+        self.blocks.entry(key).or_insert_with(Vec::new).push(block);
+        buf.truncate(bytes);
+        self.stats.record_allocation(bytes, bucket_bytes, precision, false);
+        buf
     }
 
-    fn record_allocation(&mut self, bytes: usize) {
-        self.allocated += bytes;
-        self.peak_usage = self.peak_usage.max(self.allocated);
-    }
+    fn return_buffer(&mut self, mut buffer: Vec<u8>, size: usize, precision: Precision) {
+        let bytes = precision.buffer_bytes(size);
+        let bucket = bucket_index(bytes);
+        let bucket_bytes = bucket_size(bucket);
+        let key = (bucket, precision);
 
-    fn record_deallocation(&mut self, bytes: usize) {
-        if bytes > self.allocated {
-            self.allocated = 0;
-        } else {
-            self.allocated -= bytes;
+        // Check if we should keep this buffer or drop it due to memory pressure
+        if self.allocated() < self.max_pool_size {
+            // Grow it back to the slot's full capacity so it can satisfy
+            // any future request that rounds into this bucket.
+            buffer.resize(bucket_bytes, 0);
+            if let Some(blocks) = self.blocks.get_mut(&key) {
+                if let Some(block) = blocks.iter_mut().find(|b| !b.is_fully_free()) {
+                    block.return_slot(buffer);
+                }
+
+                // Coalesce: once more than one block in this bucket sits
+                // entirely free, release all but one, so a burst of
+                // returns doesn't accumulate idle blocks without bound.
+                let mut seen_free = false;
+                blocks.retain(|b| {
+                    if b.is_fully_free() {
+                        if seen_free {
+                            return false;
+                        }
+                        seen_free = true;
+                    }
+                    true
+                });
+            }
         }
+
+        self.stats.record_deallocation(bytes, bucket_bytes, precision);
     }
 
-    /// Clean up fragmented buffers to reduce memory pressure
+    /// Clean up fragmented buckets to reduce memory pressure, operating
+    /// per-bucket so a single oversized bucket can't starve the others.
     fn cleanup_fragmented_buffers(&mut self) {
-        let total_buffers: usize = self.buffers.values().map(|v| v.len()).sum();
-        if total_buffers == 0 {
+        let total_blocks: usize = self.blocks.values().map(|v| v.len()).sum();
+        if total_blocks == 0 {
             return;
         }
 
-        let buffer_types = self.buffers.len();
-        let avg_buffers_per_type = total_buffers / buffer_types.max(1);
-        let fragmentation_ratio = buffer_types as f32 / total_buffers as f32;
+        let bucket_count = self.blocks.len();
+        let avg_blocks_per_bucket = total_blocks / bucket_count.max(1);
+        let fragmentation_ratio = bucket_count as f32 / total_blocks as f32;
 
         if fragmentation_ratio > self.fragmentation_threshold {
-            // Remove buffer types with only a few buffers
-            self.buffers.retain(|_, buffers| buffers.len() > avg_buffers_per_type / 2);
-            
-            // Limit number of buffers per type
-            for buffers in self.buffers.values_mut() {
-                buffers.truncate(avg_buffers_per_type * 2);
+            // Drop buckets that only hold a handful of blocks.
+            self.blocks.retain(|_, blocks| blocks.len() > avg_blocks_per_bucket / 2);
+
+            // Cap the number of cached blocks per bucket.
+            for blocks in self.blocks.values_mut() {
+                blocks.truncate(MAX_BLOCKS_PER_BUCKET.min(avg_blocks_per_bucket * 2));
             }
         }
+
+        *self.stats.last_cleanup.lock().unwrap() = Some(Instant::now());
     }
 
-    /// Emergency cleanup - remove all cached buffers
+    /// Emergency cleanup - remove all cached blocks
     fn emergency_cleanup(&mut self) {
-        self.buffers.clear();
-        self.allocated = 0;
+        self.blocks.clear();
+        self.stats.allocated.store(0, Ordering::Release);
+        self.stats.fragmentation_bytes.store(0, Ordering::Release);
+        *self.stats.last_cleanup.lock().unwrap() = Some(Instant::now());
+    }
+
+    /// Total bytes resident across every block (idle or checked out), i.e.
+    /// the pool's actual memory footprint rather than just what's currently
+    /// handed out — a burst of returns that all stay cached would otherwise
+    /// never register as pressure at all.
+    fn resident_bytes(&self) -> usize {
+        self.blocks.values().flatten().map(Block::resident_bytes).sum()
     }
 
     /// Get memory pressure ratio (0.0 = no pressure, 1.0 = at limit)
     fn memory_pressure(&self) -> f32 {
-        self.allocated as f32 / self.max_pool_size as f32
+        self.resident_bytes() as f32 / self.max_pool_size as f32
+    }
+
+    /// Configure the watermarks `get_buffer` uses to decide when to run
+    /// `evict_lru` and how far to bring pressure down once it does.
+    fn set_watermarks(&mut self, high: f32, low: f32) {
+        self.high_watermark = high;
+        self.low_watermark = low;
+    }
+
+    /// Evict least-recently-returned free slots, coldest first, until
+    /// `memory_pressure` drops to `low_watermark` or there's nothing left to
+    /// evict. Never touches a slot that's currently checked out — those
+    /// aren't in any block's `free` list to begin with, since this pool only
+    /// hands callers an owned `Vec<u8>` it can't reach into while they hold
+    /// it. Returns the number of bytes reclaimed.
+    fn evict_lru(&mut self) -> usize {
+        let mut reclaimed = 0usize;
+        while self.memory_pressure() > self.low_watermark {
+            let victim = self.blocks.iter().flat_map(|(&key, blocks)| {
+                blocks.iter().enumerate().filter_map(move |(block_idx, block)| {
+                    block.lru_free_slot().map(|slot_idx| (key, block_idx, slot_idx, block.free[slot_idx].2))
+                })
+            });
+
+            let Some((key, block_idx, slot_idx, _)) = victim.min_by_key(|&(_, _, _, last_used)| last_used) else {
+                break;
+            };
+
+            let blocks = self.blocks.get_mut(&key).unwrap();
+            let block = &mut blocks[block_idx];
+            reclaimed += block.evict_slot(slot_idx);
+            if block.capacity == 0 {
+                blocks.remove(block_idx);
+            }
+            if blocks.is_empty() {
+                self.blocks.remove(&key);
+            }
+        }
+        reclaimed
+    }
+
+    /// Release every fully-idle backing block back to the driver, returning
+    /// the bytes reclaimed. Unlike `evict_lru`, this only ever removes whole
+    /// blocks that are already entirely free — it can't relocate a live
+    /// sub-allocation to coalesce a partially-used block, since its buffer is
+    /// an owned `Vec<u8>` the caller already holds, not something this pool
+    /// can move out from under them.
+    fn defragment(&mut self) -> usize {
+        let mut reclaimed = 0usize;
+        for blocks in self.blocks.values_mut() {
+            blocks.retain(|block| {
+                if block.is_fully_free() {
+                    reclaimed += block.resident_bytes();
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+        self.blocks.retain(|_, blocks| !blocks.is_empty());
+        *self.stats.last_cleanup.lock().unwrap() = Some(Instant::now());
+        reclaimed
     }
 
     /// Set maximum pool size
     fn set_max_pool_size(&mut self, max_size: usize) {
         self.max_pool_size = max_size;
-        if self.allocated > max_size {
+        if self.allocated() > max_size {
             self.cleanup_fragmented_buffers();
         }
     }
 }
 
+/// A device's allocation pool plus the lock-free stats handle shared with it.
+#[derive(Clone)]
+struct DevicePoolEntry {
+    pool: Arc<Mutex<DeviceMemoryPool>>,
+    stats: Arc<DeviceStats>,
+}
+
+impl DevicePoolEntry {
+    fn new() -> Self {
+        let stats = Arc::new(DeviceStats::default());
+        Self {
+            pool: Arc::new(Mutex::new(DeviceMemoryPool::new(stats.clone()))),
+            stats,
+        }
+    }
+}
+
 #[derive(Clone)]
-pub struct GpuMemoryPool(Arc<Mutex<HashMap<usize, Arc<Mutex<DeviceMemoryPool>>>>>);
+pub struct GpuMemoryPool(Arc<Mutex<HashMap<usize, DevicePoolEntry>>>);
 
 impl GpuMemoryPool {
     pub fn new() -> Self {
         Self(Arc::new(Mutex::new(HashMap::new())))
     }
 
+    /// Get or create the per-device pool behind its shared lock.
+    fn device_pool(&self, device_id: usize) -> Arc<Mutex<DeviceMemoryPool>> {
+        let mut pools = self.0.lock().unwrap();
+        pools.entry(device_id).or_insert_with(DevicePoolEntry::new).pool.clone()
+    }
+
+    /// Get or create the per-device stats handle. Cheap and lock-free to use
+    /// afterwards: callers can hold on to the returned `Arc` and poll it
+    /// without ever touching the per-device allocation mutex again.
+    fn device_stats_handle(&self, device_id: usize) -> Arc<DeviceStats> {
+        let mut pools = self.0.lock().unwrap();
+        pools.entry(device_id).or_insert_with(DevicePoolEntry::new).stats.clone()
+    }
+
     pub fn get_buffer(&self, device_id: usize, size: usize, precision: Precision) -> Vec<u8> {
-        let pool = {
-            let mut pools = self.0.lock().unwrap();
-            pools.entry(device_id).or_insert_with(|| Arc::new(Mutex::new(DeviceMemoryPool::new()))).clone()
-        };
+        let pool = self.device_pool(device_id);
         let mut pool = pool.lock().unwrap();
         pool.get_buffer(size, precision)
     }
@@ -257,57 +714,143 @@ impl GpuMemoryPool {
         GpuBuffer::new(buffer, device_id, size, precision, Arc::new(Mutex::new(self.clone())))
     }
 
+    /// Get a RAII-wrapped buffer, pre-charging its byte size against `reservation`.
+    /// Fails with `OutOfPoolMemory` rather than over-allocating if the
+    /// reservation's device is already at its pool limit.
+    pub fn try_get_managed_buffer(
+        &self,
+        reservation: &mut MemoryReservation,
+        size: usize,
+        precision: Precision,
+    ) -> Result<GpuBuffer, GpuError> {
+        let bytes = precision.buffer_bytes(size);
+        reservation.try_grow(bytes)?;
+        Ok(self.get_managed_buffer(reservation.device_id, size, precision))
+    }
+
     /// Create a batch of managed buffers
     pub fn create_buffer_batch(&self, device_id: usize) -> GpuBufferBatch {
         GpuBufferBatch::new(device_id, Arc::new(Mutex::new(self.clone())))
     }
 
+    /// Create a batch of managed buffers bound to `reservation`'s device,
+    /// so the caller can pre-reserve its working set with `try_grow` and
+    /// have every `GpuBuffer` added via `try_add_buffer` accounted for.
+    pub fn create_reserved_buffer_batch(&self, reservation: &MemoryReservation) -> GpuBufferBatch {
+        self.create_buffer_batch(reservation.device_id)
+    }
+
+    /// Create a reservation handle for `device_id`. Callers should `try_grow`
+    /// their expected working set before allocating, and may `shrink` as
+    /// buffers are released; any outstanding amount is freed on `Drop`.
+    pub fn reserve(&self, device_id: usize) -> MemoryReservation {
+        MemoryReservation {
+            device_id,
+            pool: self.clone(),
+            reserved_bytes: 0,
+        }
+    }
+
     pub fn return_buffer(&self, device_id: usize, buffer: Vec<u8>, size: usize, precision: Precision) {
-        if let Some(pool) = {
+        if let Some(entry) = {
             let pools = self.0.lock().unwrap();
             pools.get(&device_id).cloned()
         } {
-            let mut pool = pool.lock().unwrap();
+            let mut pool = entry.pool.lock().unwrap();
             pool.return_buffer(buffer, size, precision);
         }
     }
 
     pub fn memory_usage(&self, device_id: usize) -> Option<(usize, usize)> {
-        let pool = {
+        let stats = {
             let pools = self.0.lock().unwrap();
-            pools.get(&device_id).cloned()
+            pools.get(&device_id).map(|entry| entry.stats.clone())
         }?;
-        let pool = pool.lock().unwrap();
-        Some((pool.allocated, pool.peak_usage))
+        let snapshot = stats.snapshot();
+        Some((snapshot.allocated, snapshot.peak_usage))
     }
 
     /// Get memory pressure for a device (0.0 = no pressure, 1.0 = at limit)
     pub fn memory_pressure(&self, device_id: usize) -> Option<f32> {
-        let pool = {
+        let entry = {
             let pools = self.0.lock().unwrap();
             pools.get(&device_id).cloned()
         }?;
-        let pool = pool.lock().unwrap();
+        let pool = entry.pool.lock().unwrap();
         Some(pool.memory_pressure())
     }
 
     /// Set maximum pool size for a device
     pub fn set_max_pool_size(&self, device_id: usize, max_size: usize) {
-        let pool = {
+        let entry = {
             let mut pools = self.0.lock().unwrap();
-            pools.entry(device_id).or_insert_with(|| Arc::new(Mutex::new(DeviceMemoryPool::new()))).clone()
+            pools.entry(device_id).or_insert_with(DevicePoolEntry::new).clone()
         };
-        let mut pool = pool.lock().unwrap();
+        let mut pool = entry.pool.lock().unwrap();
         pool.set_max_pool_size(max_size);
     }
 
+    /// Set the backing block size (as `2^log2` bytes) that the segregated-list
+    /// sub-allocator carves into slots for a device; takes effect for blocks
+    /// carved after the call. Defaults to [`DEFAULT_BLOCK_SIZE_LOG2`] (64 MiB).
+    pub fn set_block_size_log2(&self, device_id: usize, log2: u32) {
+        let entry = {
+            let mut pools = self.0.lock().unwrap();
+            pools.entry(device_id).or_insert_with(DevicePoolEntry::new).clone()
+        };
+        let mut pool = entry.pool.lock().unwrap();
+        pool.set_block_size_log2(log2);
+    }
+
+    /// Bytes currently lost to power-of-two bucket rounding for a device,
+    /// i.e. the gap between what callers asked for and the slot size they
+    /// were actually handed.
+    pub fn fragmentation_bytes(&self, device_id: usize) -> Option<usize> {
+        self.get_device_stats(device_id).map(|stats| stats.fragmentation_bytes)
+    }
+
+    /// Configure the high/low pressure watermarks a device's pool uses to
+    /// decide when `get_buffer` should run `evict_lru` and how far to bring
+    /// pressure down once it does. Defaults to 0.85/0.6.
+    pub fn set_watermarks(&self, device_id: usize, high: f32, low: f32) {
+        let entry = {
+            let mut pools = self.0.lock().unwrap();
+            pools.entry(device_id).or_insert_with(DevicePoolEntry::new).clone()
+        };
+        let mut pool = entry.pool.lock().unwrap();
+        pool.set_watermarks(high, low);
+    }
+
+    /// Evict a device's least-recently-returned cached slots, coldest first,
+    /// until its memory pressure drops to the low watermark, without
+    /// touching live checked-out allocations. Returns bytes reclaimed, so a
+    /// caller that just hit `OutOfPoolMemory` can decide whether it's worth
+    /// retrying the allocation. Normally triggered automatically by
+    /// `get_buffer` once pressure crosses the high watermark; exposed here
+    /// for callers that want to force a pass (e.g. before a large reservation).
+    pub fn evict_lru(&self, device_id: usize) -> usize {
+        let pool = self.device_pool(device_id);
+        let mut pool = pool.lock().unwrap();
+        pool.evict_lru()
+    }
+
+    /// Release every fully-idle backing block for a device back to the
+    /// driver, returning the bytes reclaimed. Complements `evict_lru`: where
+    /// eviction drops individual cold slots, `defragment` sweeps up whole
+    /// blocks that eviction (or a burst of returns) already left entirely free.
+    pub fn defragment(&self, device_id: usize) -> usize {
+        let pool = self.device_pool(device_id);
+        let mut pool = pool.lock().unwrap();
+        pool.defragment()
+    }
+
     /// Emergency cleanup for a device - removes all cached buffers
     pub fn emergency_cleanup(&self, device_id: usize) {
-        if let Some(pool) = {
+        if let Some(entry) = {
             let pools = self.0.lock().unwrap();
             pools.get(&device_id).cloned()
         } {
-            let mut pool = pool.lock().unwrap();
+            let mut pool = entry.pool.lock().unwrap();
             pool.emergency_cleanup();
         }
     }
@@ -315,8 +858,8 @@ impl GpuMemoryPool {
     /// Emergency cleanup for all devices
     pub fn emergency_cleanup_all(&self) {
         let pools = self.0.lock().unwrap();
-        for pool in pools.values() {
-            let mut pool = pool.lock().unwrap();
+        for entry in pools.values() {
+            let mut pool = entry.pool.lock().unwrap();
             pool.emergency_cleanup();
         }
     }
@@ -326,13 +869,315 @@ impl GpuMemoryPool {
         let pools = self.0.lock().unwrap();
         let mut total_allocated = 0;
         let mut total_peak = 0;
-        
-        for pool in pools.values() {
-            let pool = pool.lock().unwrap();
-            total_allocated += pool.allocated;
-            total_peak += pool.peak_usage;
+
+        for entry in pools.values() {
+            let snapshot = entry.stats.snapshot();
+            total_allocated += snapshot.allocated;
+            total_peak += snapshot.peak_usage;
         }
-        
+
         (total_allocated, total_peak)
     }
+
+    /// Lock-free allocated byte count for a device, read directly off the
+    /// atomic counter without locking the device's allocation mutex.
+    pub fn allocated_atomic(&self, device_id: usize) -> Option<usize> {
+        let pools = self.0.lock().unwrap();
+        pools.get(&device_id).map(|entry| entry.stats.allocated.load(Ordering::Acquire))
+    }
+
+    /// Snapshot of a device's allocation statistics, read without locking
+    /// the device's allocation mutex.
+    pub fn get_device_stats(&self, device_id: usize) -> Option<MemoryStats> {
+        let pools = self.0.lock().unwrap();
+        pools.get(&device_id).map(|entry| entry.stats.snapshot())
+    }
+
+    /// Fraction of a device's buffer requests served from cache, in `[0.0, 1.0]`.
+    pub fn cache_efficiency(&self, device_id: usize) -> Option<f32> {
+        self.get_device_stats(device_id).map(|stats| stats.cache_efficiency())
+    }
+
+    /// Snapshot of every known device's statistics, keyed by device id.
+    pub fn summary_stats(&self) -> HashMap<usize, MemoryStats> {
+        let pools = self.0.lock().unwrap();
+        pools.iter().map(|(&device_id, entry)| (device_id, entry.stats.snapshot())).collect()
+    }
+
+    /// Allocation event log plus peak/current usage for a device, for
+    /// diagnosing OOMs and fragmentation in place of guessing from the
+    /// one-shot snapshot returned by `memory_usage`.
+    pub fn memory_report(&self, device_id: usize) -> Option<DeviceMemoryReport> {
+        let entry = {
+            let pools = self.0.lock().unwrap();
+            pools.get(&device_id).cloned()
+        }?;
+        let stats = entry.stats.snapshot();
+        let events = entry.stats.events.lock().unwrap().iter().cloned().collect();
+        Some(DeviceMemoryReport { device_id, stats, events })
+    }
+
+    /// Run fragmentation cleanup on every device whose pool hasn't been
+    /// cleaned within `min_interval`, recording `last_cleanup` for each.
+    pub fn maintenance_cleanup(&self, min_interval: std::time::Duration) {
+        let entries: Vec<DevicePoolEntry> = {
+            let pools = self.0.lock().unwrap();
+            pools.values().cloned().collect()
+        };
+        for entry in entries {
+            let due = match *entry.stats.last_cleanup.lock().unwrap() {
+                Some(last) => last.elapsed() >= min_interval,
+                None => true,
+            };
+            if due {
+                let mut pool = entry.pool.lock().unwrap();
+                pool.cleanup_fragmented_buffers();
+            }
+        }
+    }
+}
+
+/// A handle representing bytes pre-charged against a device's pool limit,
+/// letting callers (e.g. a batch k-NN search) claim their working set up
+/// front and fail fast instead of overrunning `max_pool_size`.
+pub struct MemoryReservation {
+    device_id: usize,
+    pool: GpuMemoryPool,
+    reserved_bytes: usize,
+}
+
+impl MemoryReservation {
+    /// Device this reservation tracks.
+    pub fn device_id(&self) -> usize {
+        self.device_id
+    }
+
+    /// Bytes currently reserved by this handle.
+    pub fn reserved(&self) -> usize {
+        self.reserved_bytes
+    }
+
+    /// Claim `bytes` more against the device's pool limit, returning
+    /// `GpuError::OutOfPoolMemory` rather than over-allocating if the
+    /// device has no room left.
+    pub fn try_grow(&mut self, bytes: usize) -> Result<(), GpuError> {
+        let pool = self.pool.device_pool(self.device_id);
+        let mut pool = pool.lock().unwrap();
+        pool.try_reserve(bytes)?;
+        self.reserved_bytes += bytes;
+        Ok(())
+    }
+
+    /// Release up to `bytes` of this reservation's claim early.
+    pub fn shrink(&mut self, bytes: usize) {
+        let bytes = bytes.min(self.reserved_bytes);
+        if bytes == 0 {
+            return;
+        }
+        let pool = self.pool.device_pool(self.device_id);
+        if let Ok(mut pool) = pool.lock() {
+            pool.release_reservation(bytes);
+        }
+        self.reserved_bytes -= bytes;
+    }
+}
+
+impl Drop for MemoryReservation {
+    fn drop(&mut self) {
+        self.shrink(self.reserved_bytes);
+    }
+}
+
+/// Maximum number of outgrown chunks `CpuToGpuRingPool` keeps around hoping
+/// to recycle one; excess idle chunks are simply dropped instead of growing
+/// this list without bound.
+const MAX_RETIRED_RING_CHUNKS: usize = 4;
+
+/// One chunk of backing storage for a `CpuToGpuRingPool`: a fixed-capacity
+/// byte buffer plus a bump `write_offset` that `try_bump` advances, and an
+/// `outstanding` count of live `RingRegion`s carved out of it so the pool
+/// can tell when every reference into the chunk has gone away.
+struct RingChunk {
+    data: UnsafeCell<Vec<u8>>,
+    capacity: usize,
+    write_offset: AtomicUsize,
+    outstanding: AtomicUsize,
+}
+
+// SAFETY: `try_bump` hands out disjoint, non-overlapping `[offset, offset +
+// len)` ranges (it only ever moves `write_offset` forward via CAS), so the
+// `&mut [u8]` views different `RingRegion`s take out of the same `data`
+// never alias.
+unsafe impl Sync for RingChunk {}
+
+impl RingChunk {
+    fn new(capacity: usize) -> Self {
+        Self {
+            data: UnsafeCell::new(vec![0u8; capacity]),
+            capacity,
+            write_offset: AtomicUsize::new(0),
+            outstanding: AtomicUsize::new(0),
+        }
+    }
+
+    /// Bump-allocate `len` bytes from this chunk, returning the starting
+    /// offset, or `None` if the chunk doesn't have `len` bytes left.
+    fn try_bump(&self, len: usize) -> Option<usize> {
+        let mut current = self.write_offset.load(Ordering::Acquire);
+        loop {
+            let new_offset = current.checked_add(len)?;
+            if new_offset > self.capacity {
+                return None;
+            }
+            match self.write_offset.compare_exchange_weak(
+                current,
+                new_offset,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    self.outstanding.fetch_add(1, Ordering::AcqRel);
+                    return Some(current);
+                }
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    /// # Safety
+    /// `offset..offset + len` must have come from a successful `try_bump`
+    /// on this chunk. `try_bump`'s CAS loop guarantees distinct calls never
+    /// return overlapping ranges, so distinct `RingRegion`s never alias.
+    unsafe fn slice(&self, offset: usize, len: usize) -> &[u8] {
+        let ptr = (*self.data.get()).as_ptr().add(offset);
+        std::slice::from_raw_parts(ptr, len)
+    }
+
+    /// # Safety
+    /// Same precondition as [`Self::slice`].
+    unsafe fn slice_mut(&self, offset: usize, len: usize) -> &mut [u8] {
+        let ptr = (*self.data.get()).as_mut_ptr().add(offset);
+        std::slice::from_raw_parts_mut(ptr, len)
+    }
+
+    fn release(&self) {
+        self.outstanding.fetch_sub(1, Ordering::AcqRel);
+    }
+
+    fn is_idle(&self) -> bool {
+        self.outstanding.load(Ordering::Acquire) == 0
+    }
+}
+
+/// A contiguous sub-region bump-allocated out of a `CpuToGpuRingPool` chunk,
+/// for staging one query/batch's bytes ahead of a DMA upload without a
+/// per-call heap allocation. Holding this alive pins its backing chunk (via
+/// `RingChunk::outstanding`); dropping it is what lets the pool eventually
+/// recycle that chunk once every region from it has gone the same way.
+pub struct RingRegion {
+    chunk: Arc<RingChunk>,
+    offset: usize,
+    len: usize,
+}
+
+impl RingRegion {
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe { self.chunk.slice(self.offset, self.len) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { self.chunk.slice_mut(self.offset, self.len) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Drop for RingRegion {
+    fn drop(&mut self) {
+        self.chunk.release();
+    }
+}
+
+/// Growable ring/arena allocator for host buffers about to be staged to the
+/// GPU. Repeated `next_region` calls bump-allocate contiguous sub-regions
+/// out of one backing chunk instead of a heap allocation per query, and
+/// outgrowing the current chunk's capacity grows to a fresh chunk sized to
+/// at least `max(requested_len, 2 * current_capacity)` rather than
+/// fragmenting into many same-size slabs the way `DeviceMemoryPool`'s
+/// bucket cache does. An outgrown chunk stays alive for as long as any
+/// `RingRegion` still references it, and is only ever recycled — reset and
+/// reused for a later `next_region` call — once every region from it has
+/// dropped.
+pub struct CpuToGpuRingPool {
+    current: Mutex<Arc<RingChunk>>,
+    retired: Mutex<Vec<Arc<RingChunk>>>,
+}
+
+impl CpuToGpuRingPool {
+    pub fn new(initial_capacity: usize) -> Self {
+        Self {
+            current: Mutex::new(Arc::new(RingChunk::new(initial_capacity.max(1)))),
+            retired: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Bump-allocate a `len`-byte region from the current chunk, growing
+    /// (or recycling a retired, now-idle chunk) if it doesn't fit.
+    pub fn next_region(&self, len: usize) -> RingRegion {
+        let mut current = self.current.lock().unwrap();
+        if let Some(offset) = current.try_bump(len) {
+            return RingRegion { chunk: current.clone(), offset, len };
+        }
+
+        let new_capacity = len.max(2 * current.capacity);
+        let outgrown = current.clone();
+
+        let fresh = {
+            let mut retired = self.retired.lock().unwrap();
+            // A retired chunk is only safe to recycle once its sole
+            // remaining owner is this list — i.e. every `RingRegion` that
+            // referenced it has dropped.
+            let reusable = retired
+                .iter()
+                .position(|c| Arc::strong_count(c) == 1 && c.capacity >= new_capacity);
+            match reusable {
+                Some(pos) => {
+                    let chunk = retired.swap_remove(pos);
+                    chunk.write_offset.store(0, Ordering::Release);
+                    chunk
+                }
+                None => Arc::new(RingChunk::new(new_capacity)),
+            }
+        };
+
+        let mut retired = self.retired.lock().unwrap();
+        retired.push(outgrown);
+        if retired.len() > MAX_RETIRED_RING_CHUNKS {
+            retired.remove(0);
+        }
+        drop(retired);
+
+        let offset = fresh
+            .try_bump(len)
+            .expect("a freshly grown or recycled chunk always fits the request that sized it");
+        *current = fresh.clone();
+        RingRegion { chunk: fresh, offset, len }
+    }
+
+    /// Whether the chunk currently being bump-allocated from has every
+    /// region handed out of it returned (nothing is still staging against it).
+    pub fn is_current_idle(&self) -> bool {
+        self.current.lock().unwrap().is_idle()
+    }
+
+    /// Byte capacity of the chunk currently being bump-allocated from.
+    pub fn current_capacity(&self) -> usize {
+        self.current.lock().unwrap().capacity
+    }
 }
\ No newline at end of file
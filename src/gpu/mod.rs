@@ -0,0 +1,215 @@
+//! GPU-accelerated distance backends (CUDA / ROCm) and supporting memory pool.
+
+pub mod memory;
+pub mod monitoring;
+
+#[cfg(feature = "cuda")]
+pub mod cuda;
+#[cfg(feature = "rocm")]
+pub mod rocm;
+
+use thiserror::Error;
+
+#[cfg(feature = "cuda")]
+use pyo3::prelude::*;
+
+/// Number of elements per block for the `Q8_0`/`Q4_0` block-quantized
+/// precisions, matching GGML's convention.
+pub const QBLOCK_SIZE: usize = 32;
+
+/// Element precision used for GPU-side buffers and kernels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Precision {
+    Fp32,
+    Fp16,
+    Int8,
+    /// GGML Q8_0-style block quantization: each `QBLOCK_SIZE`-element block
+    /// stores one fp16 scale `d = max(|x_i|)/127` followed by `QBLOCK_SIZE`
+    /// int8 codes `round(x_i / d)`.
+    Q8_0,
+    /// GGML Q4_0-style block quantization: each `QBLOCK_SIZE`-element block
+    /// stores one fp16 scale `d = max(|x_i|)/7` followed by `QBLOCK_SIZE / 2`
+    /// bytes of two 4-bit codes each.
+    Q4_0,
+}
+
+impl Precision {
+    /// Size in bytes of a single element at this precision. Not meaningful
+    /// for the block-quantized formats, whose byte layout has per-block
+    /// overhead that doesn't divide evenly per element — use
+    /// [`Precision::buffer_bytes`] for exact sizing of those.
+    pub fn element_size(self) -> usize {
+        match self {
+            Precision::Fp32 => 4,
+            Precision::Fp16 => 2,
+            Precision::Int8 => 1,
+            Precision::Q8_0 | Precision::Q4_0 => 1,
+        }
+    }
+
+    /// Exact byte size of a buffer holding `count` scalar elements at this
+    /// precision, accounting for the per-block fp16 scale header of the
+    /// block-quantized formats, and the single per-row fp16 scale header
+    /// `Int8` carries (one row == one `count`-element call, matching how
+    /// callers invoke this with `count = dim`), rather than assuming a fixed
+    /// bytes-per-element ratio.
+    pub fn buffer_bytes(self, count: usize) -> usize {
+        match self {
+            Precision::Int8 => 2 + count,
+            Precision::Q8_0 => {
+                let blocks = (count + QBLOCK_SIZE - 1) / QBLOCK_SIZE;
+                blocks * (2 + QBLOCK_SIZE)
+            }
+            Precision::Q4_0 => {
+                let blocks = (count + QBLOCK_SIZE - 1) / QBLOCK_SIZE;
+                blocks * (2 + QBLOCK_SIZE / 2)
+            }
+            _ => count * self.element_size(),
+        }
+    }
+}
+
+/// Errors that can occur in GPU backends and memory management.
+#[derive(Debug, Error)]
+pub enum GpuError {
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+    #[error("Allocation error: {0}")]
+    Allocation(String),
+    #[error("No such GPU device: {0}")]
+    DeviceIndex(usize),
+    #[error("Out of pool memory: requested {requested} bytes, {available} available")]
+    OutOfPoolMemory { requested: usize, available: usize },
+    #[cfg(feature = "cuda")]
+    #[error("CUDA error: {0}")]
+    Cuda(#[from] cust::error::CudaError),
+    #[cfg(feature = "rocm")]
+    #[error("HIP error: {0}")]
+    Hip(#[from] hip_runtime::error::HipError),
+}
+
+/// Common interface implemented by each GPU distance backend.
+pub trait GpuBackend {
+    /// Compute pairwise L2 distances between `queries` and `corpus`.
+    fn l2_distance(
+        queries: &[f32],
+        corpus: &[f32],
+        dim: usize,
+        n_queries: usize,
+        n_vectors: usize,
+        device_id: usize,
+        precision: Precision,
+    ) -> Result<Vec<f32>, GpuError>;
+
+    /// Current (allocated, peak) memory usage for a device, in bytes.
+    fn memory_usage(device_id: usize) -> Result<(usize, usize), GpuError>;
+
+    /// Number of GPU devices visible to this backend.
+    fn device_count() -> usize;
+
+    /// Fused on-device top-k search: the full `n_queries * n_vectors`
+    /// distance matrix is computed and reduced to its k smallest
+    /// (id, distance) pairs per query without ever leaving device memory,
+    /// so the D2H transfer shrinks to `n_queries * k` instead of the whole
+    /// matrix that `l2_distance` copies back for host-side selection.
+    fn search(
+        queries: &[f32],
+        corpus: &[f32],
+        dim: usize,
+        n_queries: usize,
+        n_vectors: usize,
+        k: usize,
+        device_id: usize,
+        precision: Precision,
+    ) -> Result<Vec<Vec<(usize, f32)>>, GpuError>;
+}
+
+/// Compute pairwise L2 distances using whichever GPU backend was compiled in.
+#[allow(unused_variables)]
+pub fn l2_distance_gpu(
+    queries: &[f32],
+    corpus: &[f32],
+    dim: usize,
+    n_queries: usize,
+    n_vectors: usize,
+    device_id: usize,
+    precision: Precision,
+) -> Result<Vec<f32>, GpuError> {
+    #[cfg(feature = "cuda")]
+    {
+        return cuda::CudaBackend::l2_distance(queries, corpus, dim, n_queries, n_vectors, device_id, precision);
+    }
+    #[cfg(all(feature = "rocm", not(feature = "cuda")))]
+    {
+        return rocm::RocmBackend::l2_distance(queries, corpus, dim, n_queries, n_vectors, device_id, precision);
+    }
+    #[cfg(not(any(feature = "cuda", feature = "rocm")))]
+    {
+        Err(GpuError::Allocation("No GPU backend compiled into this build".to_string()))
+    }
+}
+
+/// Fused top-k search using whichever GPU backend was compiled in. See
+/// [`GpuBackend::search`].
+#[allow(unused_variables)]
+pub fn search_gpu(
+    queries: &[f32],
+    corpus: &[f32],
+    dim: usize,
+    n_queries: usize,
+    n_vectors: usize,
+    k: usize,
+    device_id: usize,
+    precision: Precision,
+) -> Result<Vec<Vec<(usize, f32)>>, GpuError> {
+    #[cfg(feature = "cuda")]
+    {
+        return cuda::CudaBackend::search(queries, corpus, dim, n_queries, n_vectors, k, device_id, precision);
+    }
+    #[cfg(not(feature = "cuda"))]
+    {
+        Err(GpuError::Allocation("No GPU backend compiled into this build".to_string()))
+    }
+}
+
+/// Allocation event log plus peak/current usage for a device's slice of the
+/// global CUDA memory pool, so users can diagnose OOMs and fragmentation
+/// instead of guessing from a bare (allocated, peak) tuple.
+#[cfg(feature = "cuda")]
+#[pyfunction]
+pub fn gpu_memory_report(py: Python<'_>, device_id: usize) -> PyResult<PyObject> {
+    use pyo3::types::{PyDict, PyList};
+
+    let report = cuda::memory_pool()
+        .lock()
+        .unwrap()
+        .memory_report(device_id)
+        .ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!("no memory pool for device {device_id}"))
+        })?;
+
+    let events = PyList::empty(py);
+    for event in &report.events {
+        let (kind, cache_hit) = match event.kind {
+            memory::AllocationEventKind::Allocate { cache_hit } => ("allocate", Some(cache_hit)),
+            memory::AllocationEventKind::Deallocate => ("deallocate", None),
+        };
+        let event_dict = PyDict::new(py);
+        event_dict.set_item("size_bytes", event.size_bytes)?;
+        event_dict.set_item("precision", format!("{:?}", event.precision))?;
+        event_dict.set_item("operation", &event.operation)?;
+        event_dict.set_item("kind", kind)?;
+        event_dict.set_item("cache_hit", cache_hit)?;
+        event_dict.set_item("elapsed_secs", event.timestamp.elapsed().as_secs_f64())?;
+        events.append(event_dict)?;
+    }
+
+    let result = PyDict::new(py);
+    result.set_item("device_id", report.device_id)?;
+    result.set_item("allocated", report.stats.allocated)?;
+    result.set_item("peak_usage", report.stats.peak_usage)?;
+    result.set_item("fragmentation_bytes", report.stats.fragmentation_bytes)?;
+    result.set_item("cache_efficiency", report.stats.cache_efficiency())?;
+    result.set_item("events", events)?;
+    Ok(result.into())
+}
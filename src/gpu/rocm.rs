@@ -1,62 +1,135 @@
-use hip_runtime::{hip, memory::DeviceBuffer, stream::Stream};
-use crate::gpu::GpuError;
-
-pub struct RocmBackend;
-
-impl super::GpuBackend for RocmBackend {
-    fn l2_distance(
-        queries: &[f32],
-        corpus: &[f32],
-        dim: usize,
-        n_queries: usize,
-        n_vectors: usize,
-    ) -> Result<Vec<f32>, GpuError> {
-        // Initialize HIP
-        hip::init()?;
-
-        // Create HIP kernel (needs to be precompiled)
-        let module = hip::Module::load_from_file("kernels/l2_kernel.hsaco")?;
-        let kernel = module.get_function("l2_distance_kernel")?;
-
-        // Allocate device memory
-        let d_queries = DeviceBuffer::from_slice(queries)?;
-        let d_corpus = DeviceBuffer::from_slice(corpus)?;
-        let mut d_out = DeviceBuffer::uninitialized(n_queries * n_vectors)?;
-
-        // Set kernel parameters
-        let mut args = [
-            &d_queries as *const _ as *mut _,
-            &d_corpus as *const _ as *mut _,
-            &d_out as *const _ as *mut _,
-            &(n_queries as i32),
-            &(n_vectors as i32),
-            &(dim as i32),
-        ];
-
-        // Launch kernel
-        let grid_size = n_queries as u32;
-        let block_size = n_vectors as u32;
-        let stream = Stream::new(hip::StreamFlags::NON_BLOCKING, None)?;
-        
-        unsafe {
-            kernel.launch(
-                &mut args as *mut _ as *mut *mut _,
-                grid_size,
-                1,
-                1,
-                block_size,
-                1,
-                1,
-                0,
-                Some(&stream),
-            )?;
-        }
-
-        // Copy results back
-        let mut out = vec![0.0f32; n_queries * n_vectors];
-        d_out.copy_to(&mut out)?;
-        stream.synchronize()?;
-
-        Ok(out)
-    }
-}
\ No newline at end of file
+use hip_runtime::{hip, memory::DeviceBuffer, module::{Function, Module}, stream::Stream};
+use lazy_static::lazy_static;
+use std::sync::Mutex;
+
+use crate::gpu::GpuError;
+
+/// Long-lived ROCm execution context: the initialized HIP device, the loaded
+/// `Module`, its resolved kernel `Function`, and one reusable non-blocking
+/// `Stream`. Constructing this is expensive (device init + kernel load), so
+/// callers should build it once and reuse it across `search`/`search_batch`
+/// calls rather than recreating it per query batch.
+pub struct RocmBackend {
+    module: Module,
+    kernel: Function,
+    stream: Stream,
+}
+
+impl RocmBackend {
+    /// Initialize HIP, load the precompiled L2 kernel, and open one reusable
+    /// stream. Expensive — call once and hold on to the result.
+    pub fn new() -> Result<Self, GpuError> {
+        hip::init()?;
+        let module = hip::Module::load_from_file("kernels/l2_kernel.hsaco")?;
+        let kernel = module.get_function("l2_distance_kernel")?;
+        let stream = Stream::new(hip::StreamFlags::NON_BLOCKING, None)?;
+        Ok(Self { module, kernel, stream })
+    }
+
+    /// Check whether the cached device/module/stream state is still usable,
+    /// re-initializing it if not. Returns whether the backend is healthy
+    /// (and therefore safe to use) after the call.
+    pub fn reset(&mut self) -> bool {
+        if self.stream.query().is_ok() {
+            return true;
+        }
+        match Self::new() {
+            Ok(fresh) => {
+                *self = fresh;
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Compute pairwise L2 distances using the cached module/kernel/stream.
+    pub fn l2_distance(
+        &self,
+        queries: &[f32],
+        corpus: &[f32],
+        dim: usize,
+        n_queries: usize,
+        n_vectors: usize,
+    ) -> Result<Vec<f32>, GpuError> {
+        // Allocate device memory
+        let d_queries = DeviceBuffer::from_slice(queries)?;
+        let d_corpus = DeviceBuffer::from_slice(corpus)?;
+        let mut d_out = DeviceBuffer::uninitialized(n_queries * n_vectors)?;
+
+        // Set kernel parameters
+        let mut args = [
+            &d_queries as *const _ as *mut _,
+            &d_corpus as *const _ as *mut _,
+            &d_out as *const _ as *mut _,
+            &(n_queries as i32),
+            &(n_vectors as i32),
+            &(dim as i32),
+        ];
+
+        // Launch kernel on the cached stream
+        let grid_size = n_queries as u32;
+        let block_size = n_vectors as u32;
+
+        unsafe {
+            self.kernel.launch(
+                &mut args as *mut _ as *mut *mut _,
+                grid_size,
+                1,
+                1,
+                block_size,
+                1,
+                1,
+                0,
+                Some(&self.stream),
+            )?;
+        }
+
+        // Copy results back
+        let mut out = vec![0.0f32; n_queries * n_vectors];
+        d_out.copy_to(&mut out)?;
+        self.stream.synchronize()?;
+
+        Ok(out)
+    }
+}
+
+lazy_static! {
+    /// Process-wide cached backend so code paths that only have access to
+    /// the `GpuBackend` trait (no owned `RocmBackend` instance) still reuse
+    /// the module/kernel/stream instead of reinitializing per call.
+    static ref SHARED_BACKEND: Mutex<Option<RocmBackend>> = Mutex::new(None);
+}
+
+impl super::GpuBackend for RocmBackend {
+    fn l2_distance(
+        queries: &[f32],
+        corpus: &[f32],
+        dim: usize,
+        n_queries: usize,
+        n_vectors: usize,
+        _device_id: usize,
+        _precision: crate::gpu::Precision,
+    ) -> Result<Vec<f32>, GpuError> {
+        let mut guard = SHARED_BACKEND.lock().unwrap();
+        if guard.is_none() {
+            *guard = Some(RocmBackend::new()?);
+        }
+        let backend = guard.as_mut().unwrap();
+        if !backend.reset() {
+            return Err(GpuError::Allocation("ROCm device unavailable".to_string()));
+        }
+        backend.l2_distance(queries, corpus, dim, n_queries, n_vectors)
+    }
+
+    fn memory_usage(_device_id: usize) -> Result<(usize, usize), GpuError> {
+        Err(GpuError::Allocation("ROCm memory accounting not implemented".to_string()))
+    }
+
+    fn device_count() -> usize {
+        if hip::init().is_ok() {
+            1
+        } else {
+            0
+        }
+    }
+}
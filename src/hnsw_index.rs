@@ -5,7 +5,7 @@ use serde::{Serialize, Deserialize};
 use hnsw_rs::prelude::*;
 use crate::backend::AnnBackend;
 use crate::metrics::Distance;
-use crate::utils::validate_path;
+use crate::path_validation::validate_path_secure;
 use crate::errors::RustAnnError;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -122,7 +122,11 @@ impl AnnBackend for HnswIndex {
     }
 
     fn save(&self, path: &str) {
-        let safe_path = validate_path(path).expect("Invalid or unsafe file path");
+        // `validate_path_secure` replaces the weak `..`/`/`-substring check
+        // `utils::validate_path` used to run here — that missed URL-encoded
+        // traversal, null bytes, and double-encoding, all of which the
+        // shared validator already rejects for `AnnIndex`.
+        let safe_path = validate_path_secure(path).expect("Invalid or unsafe file path");
 
         let data = HnswIndexData {
             dims: self.dims,
@@ -136,7 +140,7 @@ impl AnnBackend for HnswIndex {
     }
 
     fn load(path: &str) -> Self {
-        let safe_path = validate_path(path).expect("Invalid or unsafe file path");
+        let safe_path = validate_path_secure(path).expect("Invalid or unsafe file path");
 
         let file = File::open(&safe_path).expect("Failed to open file");
         let reader = BufReader::new(file);
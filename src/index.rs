@@ -4,7 +4,7 @@ use ndarray::Array2;
 use rayon::prelude::*;
 use serde::{Serialize, Deserialize};
 use std::sync::{Arc, Mutex};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
 use bit_vec::BitVec;
 
@@ -13,8 +13,9 @@ use crate::storage::{save_index, load_index};
 use crate::metrics::Distance;
 use crate::errors::RustAnnError;
 use crate::filters::Filter;
+use crate::metadata_filter::MetadataPredicate;
 use crate::monitoring::MetricsCollector;
-use crate::path_validation::validate_path_secure;
+use crate::path_validation::ValidatedPath;
 #[pyclass]
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub enum MetadataType {
@@ -66,6 +67,18 @@ impl MetadataValue {
     }
 }
 
+/// The placeholder value used for a field whose metadata wasn't supplied
+/// (e.g. a batch that didn't mention it, or a tombstoned entry).
+fn default_metadata_value(field_type: &MetadataType) -> MetadataValue {
+    match field_type {
+        MetadataType::Int => MetadataValue::Int(0),
+        MetadataType::Float => MetadataValue::Float(0.0),
+        MetadataType::String => MetadataValue::String(String::new()),
+        MetadataType::Tags => MetadataValue::Tags(Vec::new()),
+        MetadataType::Timestamp => MetadataValue::Timestamp(0),
+    }
+}
+
 #[pyclass]
 #[derive(Serialize, Deserialize)]
 /// A brute-force k-NN index with cached norms, Rayon parallelism,
@@ -94,6 +107,34 @@ pub struct AnnIndex {
     pub(crate) metadata_columns: Option<HashMap<String, Vec<MetadataValue>>>,
 }
 
+/// Cloning an index snapshots its searchable state: `boolean_filters` is
+/// copied out from behind its mutex and `version` becomes an independent
+/// counter frozen at the source's current value. Used by
+/// [`AnnIndex::snapshot`] to hand out lock-free, point-in-time readable
+/// copies for `ThreadSafeAnnIndex`'s MVCC search path.
+impl Clone for AnnIndex {
+    fn clone(&self) -> Self {
+        let boolean_filters = self
+            .boolean_filters
+            .lock()
+            .map(|guard| guard.clone())
+            .unwrap_or_default();
+        AnnIndex {
+            dim: self.dim,
+            metric: self.metric,
+            minkowski_p: self.minkowski_p,
+            entries: self.entries.clone(),
+            deleted_count: self.deleted_count,
+            max_deleted_ratio: self.max_deleted_ratio,
+            metrics: self.metrics.clone(),
+            boolean_filters: Mutex::new(boolean_filters),
+            version: Arc::new(AtomicU64::new(self.version.load(AtomicOrdering::Relaxed))),
+            metadata_schema: self.metadata_schema.clone(),
+            metadata_columns: self.metadata_columns.clone(),
+        }
+    }
+}
+
 #[pymethods]
 impl AnnIndex {
     /// Set metadata schema from Python (dict: str -> MetadataField)
@@ -160,32 +201,68 @@ impl AnnIndex {
         self.search_filtered(query, k, predicate)
     }
     
-    /// Search with metadata-aware filtering using a predicate string
-    pub fn search_filtered(&self, query: Vec<f32>, k: usize, _predicate: &str) -> PyResult<(Vec<i64>, Vec<f32>)> {
-        // Simple stub implementation for now - just return normal search results
-        // TODO: Implement full predicate evaluation
+    /// Search with metadata-aware filtering using a predicate string, e.g.
+    /// `price > 10.0 AND category = "shoes" AND "red" IN tags`. An empty
+    /// predicate behaves like an unfiltered search. Scores candidates under
+    /// `self.metric` (the same dispatch `search`/`search_batch` use via
+    /// `inner_search`/`search_batch_inner`), rather than always Euclidean.
+    pub fn search_filtered(&self, query: Vec<f32>, k: usize, predicate: &str) -> PyResult<(Vec<i64>, Vec<f32>)> {
         if query.len() != self.dim {
             return Err(RustAnnError::py_err("Dimension Error", format!("Expected dimension {}, got {}", self.dim, query.len())));
         }
-        
-        // For now, just do a normal search without filtering 
-        // (predicate evaluation will be added in next iterations)
+
+        let predicate_ast = if predicate.trim().is_empty() {
+            None
+        } else {
+            let schema = self.metadata_schema.clone().unwrap_or_default();
+            Some(MetadataPredicate::parse(predicate, &schema).map_err(|e| e.into_pyerr())?)
+        };
+
+        // This scan is already sequential (no `par_iter`), so a
+        // Python-backed `Custom` metric can be called inline without the
+        // GIL-reacquisition cost `inner_search`/`search_batch_inner` avoid
+        // by falling back to a sequential scan for it.
+        let custom_distance_fn = match &self.metric {
+            Distance::Custom(name) => Some(
+                crate::distance_registry::get_distance_function_safe(name).map_err(PyErr::from)?,
+            ),
+            _ => None,
+        };
+        let q_sq: f32 = query.iter().map(|v| v * v).sum();
+
         let mut results: Vec<(i64, f32)> = self.entries
             .iter()
-            .filter_map(|entry_opt| {
-                if let Some((id, vector, _norm)) = entry_opt {
-                    // Simple Euclidean distance
-                    let dist = query.iter().zip(vector.iter())
-                        .map(|(a, b)| (a - b) * (a - b))
-                        .sum::<f32>()
-                        .sqrt();
-                    Some((*id, dist))
-                } else {
-                    None
+            .enumerate()
+            .filter_map(|(idx, entry_opt)| {
+                let (id, vector, sq_norm) = entry_opt.as_ref()?;
+                if let Some(pred) = &predicate_ast {
+                    let passes = self
+                        .metadata_columns
+                        .as_ref()
+                        .is_some_and(|columns| pred.evaluate(idx, columns));
+                    if !passes {
+                        return None;
+                    }
                 }
+                let dist = match self.metric {
+                    Distance::Euclidean() => crate::metrics::euclidean_sq(&query, vector, q_sq, *sq_norm),
+                    Distance::Cosine() => crate::metrics::angular_distance(&query, vector, q_sq, *sq_norm),
+                    Distance::Manhattan() => crate::metrics::manhattan(&query, vector),
+                    Distance::Chebyshev() => crate::metrics::chebyshev(&query, vector),
+                    Distance::Minkowski(p) => crate::metrics::minkowski(&query, vector, p),
+                    Distance::Hamming() => crate::metrics::hamming(&query, vector),
+                    Distance::Jaccard() => crate::metrics::jaccard(&query, vector),
+                    Distance::Angular() => crate::metrics::angular_distance(&query, vector, q_sq, *sq_norm),
+                    Distance::Canberra() => crate::metrics::canberra(&query, vector),
+                    Distance::Custom(_) => custom_distance_fn
+                        .as_ref()
+                        .expect("computed above whenever metric is Custom")
+                        .distance(&query, vector),
+                };
+                Some((*id, dist))
             })
             .collect();
-            
+
         results.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
         let ids = results.iter().take(k).map(|(id, _)| *id).collect();
         let dists = results.iter().take(k).map(|(_, dist)| *dist).collect();
@@ -317,14 +394,7 @@ impl AnnIndex {
                     if let Some(val) = meta.get(field) {
                         col.push(val.clone());
                     } else {
-                        // Default value for missing field
-                        col.push(match field_type {
-                            MetadataType::Int => MetadataValue::Int(0),
-                            MetadataType::Float => MetadataValue::Float(0.0),
-                            MetadataType::String => MetadataValue::String(String::new()),
-                            MetadataType::Tags => MetadataValue::Tags(Vec::new()),
-                            MetadataType::Timestamp => MetadataValue::Timestamp(0),
-                        });
+                        col.push(default_metadata_value(field_type));
                     }
                 }
                 columns.entry(field.clone()).or_insert(col);
@@ -396,6 +466,10 @@ impl AnnIndex {
         // Bump version to signal mutation
         self.version.fetch_add(1, AtomicOrdering::Relaxed);
 
+        if self.should_compact() {
+            self.compact()?;
+        }
+
         Ok(())
     }
 
@@ -439,7 +513,11 @@ impl AnnIndex {
         Ok((ids_array.unbind(), dists_array.unbind()))
     }
 
-    /// Batch queries search with optional filter.
+    /// Batch queries search with optional filter. Honors `self.metric`: for
+    /// metrics whose distance decomposes into a dot product (Euclidean,
+    /// Cosine/Angular) the whole batch is scored with one cached-norm GEMM
+    /// rather than a per-query, per-entry scalar loop; other metrics fall
+    /// back to the per-pair path.
     pub fn search_batch(
         &self,
         py: Python,
@@ -452,41 +530,12 @@ impl AnnIndex {
         if arr.ncols() != self.dim {
             return Err(RustAnnError::py_err("Dimension Error", format!("Expected shape (N, {}), got (N, {})", self.dim, arr.ncols())));
         }
-        
-        let _version = self.version.load(AtomicOrdering::Relaxed);
-        let results: Result<Vec<_>, RustAnnError> = py.allow_threads(|| {
-            let filter_ref = filter.as_ref();
-            (0..n).into_par_iter().map(|i| {
-                let row = arr.row(i).to_vec();
-                // Simple search for each row (replacing inner_search call)
-                let mut results: Vec<(i64, f32)> = self.entries
-                    .iter()
-                    .filter_map(|entry_opt| {
-                        if let Some((id, vector, _norm)) = entry_opt {
-                            if let Some(f) = filter_ref {
-                                if !f.accepts(*id, 0) { // Use 0 as index for now
-                                    return None;
-                                }
-                            }
-                            // Simple Euclidean distance
-                            let dist = row.iter().zip(vector.iter())
-                                .map(|(a, b)| (a - b) * (a - b))
-                                .sum::<f32>()
-                                .sqrt();
-                            Some((*id, dist))
-                        } else {
-                            None
-                        }
-                    })
-                    .collect();
-                    
-                results.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
-                let ids: Vec<i64> = results.iter().take(k).map(|(id, _)| *id).collect();
-                let dists: Vec<f32> = results.iter().take(k).map(|(_, dist)| *dist).collect();
-                Ok((ids, dists))
-            }).collect()
+
+        let version = self.version.load(AtomicOrdering::Relaxed);
+        let results: Result<Vec<(Vec<i64>, Vec<f32>)>, RustAnnError> = py.allow_threads(|| {
+            self.search_batch_inner(&arr, k, filter.as_ref(), version)
         });
-        
+
         let results = results.map_err(|e| e.into_pyerr())?;
         let (all_ids, all_dists): (Vec<_>, Vec<_>) = results.into_iter().unzip();
         let ids_arr = Array2::from_shape_vec((n, k), all_ids.concat())
@@ -496,19 +545,62 @@ impl AnnIndex {
     Ok((ids_arr.to_pyarray(py).into(), dists_arr.to_pyarray(py).into()))
     }
 
+    /// Single-vector k-NN search restricted to rows where `mask[i]` is
+    /// true, e.g. a boolean selection column computed upstream in Polars
+    /// or pandas and pushed down instead of constructing a [`Filter`].
+    pub fn search_masked(
+        &self,
+        py: Python,
+        query: PyReadonlyArray1<f32>,
+        k: usize,
+        mask: Vec<bool>,
+    ) -> PyResult<(PyObject, PyObject)> {
+        self.search(py, query, k, Some(Filter::boolean(mask)))
+    }
+
+    /// Batch counterpart of [`search_masked`](Self::search_masked).
+    pub fn search_batch_masked(
+        &self,
+        py: Python,
+        data: PyReadonlyArray2<f32>,
+        k: usize,
+        mask: Vec<bool>,
+    ) -> PyResult<(PyObject, PyObject)> {
+        self.search_batch(py, data, k, Some(Filter::boolean(mask)))
+    }
+
+    /// Export this index's ids, vectors, and metadata columns as an Arrow
+    /// `RecordBatch` (one pyarrow `Table` column per metadata field, plus
+    /// `id` and `vector`), so dataframe engines can filter it and push a
+    /// surviving row mask back into `search_masked`/`search_batch_masked`.
+    #[cfg(feature = "arrow")]
+    pub fn to_arrow(&self) -> PyResult<arrow::pyarrow::PyArrowType<arrow::record_batch::RecordBatch>> {
+        let batch = crate::arrow_export::index_to_record_batch(self).map_err(|e| e.into_pyerr())?;
+        Ok(arrow::pyarrow::PyArrowType(batch))
+    }
+
+    /// Tune jemalloc's dirty/muzzy page decay (milliseconds) so freed
+    /// memory from `compact()` and large `remove` batches is returned to
+    /// the OS promptly. Affects the whole process, not just this index,
+    /// since jemalloc is installed as the global allocator. Only available
+    /// when built with the `jemalloc` feature.
+    #[staticmethod]
+    #[cfg(feature = "jemalloc")]
+    pub fn set_allocator_decay_ms(dirty_decay_ms: i64, muzzy_decay_ms: i64) -> PyResult<()> {
+        crate::allocator::set_decay_ms(dirty_decay_ms, muzzy_decay_ms).map_err(|e| e.into_pyerr())
+    }
+
     /// Save index to file (.bin appended).
     pub fn save(&self, path: &str) -> PyResult<()> {
-        Self::validate_path(path)?;
-        let full = format!("{}.bin", path);
-        save_index(self, &full).map_err(|e| e.into_pyerr())
+        let validated = ValidatedPath::try_from(path)?;
+        self.save_validated(&validated)
     }
 
     #[staticmethod]
     /// Load index from file (.bin appended).
     pub fn load(path: &str) -> PyResult<Self> {
-        Self::validate_path(path)?;
-        let full = format!("{}.bin", path);
-        load_index(&full).map_err(|e| e.into_pyerr())
+        let validated = ValidatedPath::try_from(path)?;
+        Self::load_validated(&validated)
     }
 
     /// Number of entries.
@@ -532,19 +624,82 @@ impl AnnIndex {
         self.version.load(AtomicOrdering::Relaxed)
     }
 
-    /// Remove entries by IDs (not yet implemented)
-    pub fn remove(&mut self, _ids: Vec<i64>) -> PyResult<()> {
-        Err(RustAnnError::py_err("NotImplemented", "Remove operation not yet implemented"))
+    /// Remove entries by ID: tombstones each matching slot (sets it to
+    /// `None` and resets its metadata columns to their per-type default),
+    /// then auto-compacts once the deleted ratio exceeds
+    /// `max_deleted_ratio`.
+    pub fn remove(&mut self, ids: Vec<i64>) -> PyResult<()> {
+        let id_set: HashSet<i64> = ids.into_iter().collect();
+        let mut removed_indices = Vec::new();
+        for (idx, entry_opt) in self.entries.iter_mut().enumerate() {
+            let matches = entry_opt.as_ref().is_some_and(|(id, _, _)| id_set.contains(id));
+            if matches {
+                *entry_opt = None;
+                removed_indices.push(idx);
+            }
+        }
+        self.deleted_count += removed_indices.len();
+
+        if let (Some(schema), Some(columns)) = (&self.metadata_schema, self.metadata_columns.as_mut()) {
+            for (field, field_type) in schema {
+                if let Some(col) = columns.get_mut(field) {
+                    let default = default_metadata_value(field_type);
+                    for &idx in &removed_indices {
+                        if let Some(slot) = col.get_mut(idx) {
+                            *slot = default.clone();
+                        }
+                    }
+                }
+            }
+        }
+
+        self.version.fetch_add(1, AtomicOrdering::Relaxed);
+
+        if self.should_compact() {
+            self.compact()?;
+        }
+        Ok(())
     }
 
-    /// Update an entry by ID (not yet implemented)
-    pub fn update(&mut self, _id: i64, _vector: Vec<f32>) -> PyResult<()> {
-        Err(RustAnnError::py_err("NotImplemented", "Update operation not yet implemented"))
+    /// Replace the vector stored for `id` in place, recomputing its cached
+    /// squared norm.
+    pub fn update(&mut self, id: i64, vector: Vec<f32>) -> PyResult<()> {
+        if vector.len() != self.dim {
+            return Err(RustAnnError::py_err("Dimension Error", format!("Expected dimension {}, got {}", self.dim, vector.len())));
+        }
+        let entry = self.entries.iter_mut()
+            .find(|e| matches!(e, Some((existing_id, _, _)) if *existing_id == id))
+            .ok_or_else(|| RustAnnError::py_err("NotFound", format!("No entry with id {}", id)))?;
+
+        let sq_norm: f32 = vector.iter().map(|x| x * x).sum();
+        *entry = Some((id, vector, sq_norm));
+
+        self.version.fetch_add(1, AtomicOrdering::Relaxed);
+        Ok(())
     }
 
-    /// Compact the index by removing deleted entries (not yet implemented)
+    /// Rebuild `entries` dropping tombstoned (`None`) slots, rewrite every
+    /// metadata column to keep the same surviving order, and reset
+    /// `deleted_count` to 0.
     pub fn compact(&mut self) -> PyResult<()> {
-        Err(RustAnnError::py_err("NotImplemented", "Compact operation not yet implemented"))
+        let surviving_indices: Vec<usize> = self.entries.iter()
+            .enumerate()
+            .filter_map(|(idx, e)| e.is_some().then_some(idx))
+            .collect();
+
+        if let Some(columns) = self.metadata_columns.as_mut() {
+            for col in columns.values_mut() {
+                *col = surviving_indices.iter()
+                    .filter_map(|&idx| col.get(idx).cloned())
+                    .collect();
+            }
+        }
+
+        self.entries.retain(|e| e.is_some());
+        self.deleted_count = 0;
+
+        self.version.fetch_add(1, AtomicOrdering::Relaxed);
+        Ok(())
     }
 
     /// Vector dimension.
@@ -604,6 +759,11 @@ impl AnnIndex {
                 d.set_item("distance_metric", snap.distance_metric)?;
                 let recall = pyo3::types::PyDict::new(py);
                 d.set_item("recall_estimates", recall)?;
+                #[cfg(feature = "jemalloc")]
+                if let Ok(alloc_stats) = crate::allocator::stats() {
+                    d.set_item("resident_bytes", alloc_stats.resident_bytes)?;
+                    d.set_item("allocated_bytes", alloc_stats.allocated_bytes)?;
+                }
                 Ok(Some(d.into()))
             })
         } else {
@@ -716,6 +876,15 @@ impl AnnIndex {
 
 // Private methods implementation (not exposed to Python)
 impl AnnIndex {
+    /// Build an immutable, cheaply-shared snapshot of the index as of its
+    /// current version. Not exposed to Python directly; used internally by
+    /// `ThreadSafeAnnIndex` for lock-free concurrent reads, where callers
+    /// search against the returned `Arc` with no lock held for the
+    /// duration of the query.
+    pub(crate) fn snapshot(&self) -> Arc<AnnIndex> {
+        Arc::new(self.clone())
+    }
+
     fn inner_search(
         &self,
         q: &[f32],
@@ -735,16 +904,80 @@ impl AnnIndex {
             return Err(RustAnnError::py_err("Dimension Error", format!("Expected dimension {}, got {}", self.dim, q.len())));
         }
 
-        let candidates: Vec<(i64, f32)> = self.entries
+        if k == 0 {
+            return Ok((vec![], vec![]));
+        }
+
+        // `Custom` dispatches through the distance registry instead of the
+        // match below: a Python-backed callback can't run under `par_iter`
+        // without repeatedly reacquiring the GIL, so it gets a sequential
+        // scan, while a native registered function still parallelizes.
+        if let Distance::Custom(name) = &self.metric {
+            let distance_fn = crate::distance_registry::get_distance_function_safe(name)
+                .map_err(PyErr::from)?;
+
+            let heap: BinaryHeap<HeapEntry> = if distance_fn.is_python() {
+                let mut heap = BinaryHeap::new();
+                for (idx, entry_opt) in self.entries.iter().enumerate() {
+                    let Some((id, vec, _sq_norm)) = entry_opt.as_ref() else {
+                        continue;
+                    };
+                    if let Some(f) = filter {
+                        if !f.accepts(*id, idx) {
+                            continue;
+                        }
+                    }
+                    let dist = distance_fn.distance(q, vec);
+                    if dist.is_finite() && dist >= 0.0 {
+                        push_bounded(&mut heap, HeapEntry { dist, id: *id }, k);
+                    }
+                }
+                heap
+            } else {
+                self.entries
+                    .par_iter()
+                    .enumerate()
+                    .fold(BinaryHeap::new, |mut heap, (idx, entry_opt)| {
+                        let Some((id, vec, _sq_norm)) = entry_opt.as_ref() else {
+                            return heap;
+                        };
+                        if let Some(f) = filter {
+                            if !f.accepts(*id, idx) {
+                                return heap;
+                            }
+                        }
+                        let dist = distance_fn.distance(q, vec);
+                        if dist.is_finite() && dist >= 0.0 {
+                            push_bounded(&mut heap, HeapEntry { dist, id: *id }, k);
+                        }
+                        heap
+                    })
+                    .reduce(BinaryHeap::new, |mut a, b| {
+                        for entry in b {
+                            push_bounded(&mut a, entry, k);
+                        }
+                        a
+                    })
+            };
+
+            return Self::heap_into_ids_dists(heap);
+        }
+
+        // Bound each Rayon worker's accumulator to a k-element max-heap
+        // (worst distance on top) instead of collecting every surviving
+        // candidate, so peak allocation is k * num_threads rather than the
+        // whole index.
+        let heap: BinaryHeap<HeapEntry> = self.entries
             .par_iter()
             .enumerate()
-            .filter_map(|(idx, entry_opt)| {
-                // skip deleted entries
-                 let (id, vec, sq_norm) = entry_opt.as_ref()?;
+            .fold(BinaryHeap::new, |mut heap, (idx, entry_opt)| {
+                let Some((id, vec, sq_norm)) = entry_opt.as_ref() else {
+                    return heap;
+                };
                 // apply user-provided filter
                 if let Some(f) = filter {
                     if !f.accepts(*id, idx) {
-                        return None;
+                        return heap;
                     }
                 }
                 // compute the distance
@@ -758,59 +991,188 @@ impl AnnIndex {
                     Distance::Jaccard()     => crate::metrics::jaccard(q, vec),
                     Distance::Angular()     => crate::metrics::angular_distance(q, vec, q_sq, *sq_norm),
                     Distance::Canberra()    => crate::metrics::canberra(q, vec),
-                    Distance::Custom(_) => return None, // or error out
+                    Distance::Custom(_) => unreachable!("Custom is handled above before this match"),
                 };
-                Some((*id, dist))
+                push_bounded(&mut heap, HeapEntry { dist, id: *id }, k);
+                heap
             })
-            .collect();
-        
-        if candidates.is_empty() {
+            .reduce(BinaryHeap::new, |mut a, b| {
+                for entry in b {
+                    push_bounded(&mut a, entry, k);
+                }
+                a
+            });
+
+        Self::heap_into_ids_dists(heap)
+    }
+
+    /// `into_sorted_vec` yields ascending order under `HeapEntry`'s `Ord`
+    /// (best, i.e. smallest distance, first).
+    fn heap_into_ids_dists(heap: BinaryHeap<HeapEntry>) -> PyResult<(Vec<i64>, Vec<f32>)> {
+        if heap.is_empty() {
             return Ok((vec![], vec![]));
         }
+        let sorted = heap.into_sorted_vec();
+        let ids: Vec<i64> = sorted.iter().map(|e| e.id).collect();
+        let dists: Vec<f32> = sorted.iter().map(|e| e.dist).collect();
+        Ok((ids, dists))
+    }
 
-        // Use a min-heap to select top k efficiently
-        use std::cmp::Ordering;
-        
-        let k = k.min(candidates.len());
-        if k == 0 {
-            return Ok((vec![], vec![]));
+    /// GEMM-backed counterpart to [`inner_search`](Self::inner_search) for
+    /// batches. Materializes the active entries into a contiguous `X`
+    /// (m×dim) once, reusing each entry's cached squared norm, and scores
+    /// every query row against all of `X` with a single matrix multiply
+    /// when the metric decomposes that way.
+    fn search_batch_inner(
+        &self,
+        queries: &ndarray::ArrayView2<f32>,
+        k: usize,
+        filter: Option<&Filter>,
+        version: u64,
+    ) -> Result<Vec<(Vec<i64>, Vec<f32>)>, RustAnnError> {
+        if version != self.version.load(AtomicOrdering::Relaxed) {
+            return Err(RustAnnError::Message("Index modified during search operation".to_string()));
+        }
+        if queries.ncols() != self.dim {
+            return Err(RustAnnError::Dimension(format!("Expected dimension {}, got {}", self.dim, queries.ncols())));
         }
-        
-        let mut candidates = candidates;
-        let (left, mid, _) = candidates.select_nth_unstable_by(k - 1, |a, b| {
-            safe_partial_cmp(&a.1, &b.1)
-        });
 
-        // Collect and sort only the top-k candidates
-        let mut top_k = left.to_vec();
-        top_k.push(*mid);
-        top_k.sort_unstable_by(|a, b| {
-            a.1.partial_cmp(&b.1).unwrap_or_else(|| {
-                if a.1.is_nan() && b.1.is_nan() {
-                    Ordering::Equal
-                } else if a.1.is_nan() {
-                    Ordering::Greater
-                } else if b.1.is_nan() {
-                    Ordering::Less
-                } else {
-                    Ordering::Equal
+        let mut ids: Vec<i64> = Vec::new();
+        let mut rows: Vec<f32> = Vec::new();
+        let mut x_sq: Vec<f32> = Vec::new();
+        for (idx, entry_opt) in self.entries.iter().enumerate() {
+            if let Some((id, vec, sq_norm)) = entry_opt {
+                if let Some(f) = filter {
+                    if !f.accepts(*id, idx) {
+                        continue;
+                    }
                 }
-            })
-        });
+                ids.push(*id);
+                rows.extend_from_slice(vec);
+                x_sq.push(*sq_norm);
+            }
+        }
+        let m = ids.len();
+        let n = queries.nrows();
+        let x = Array2::from_shape_vec((m, self.dim), rows)
+            .map_err(|e| RustAnnError::Reshape(format!("Reshape entries failed: {}", e)))?;
 
-    // Extract results
-    let ids: Vec<i64> = top_k.iter().map(|(id, _)| *id).collect();
-    let dists: Vec<f32> = top_k.iter().map(|(_, dist)| *dist).collect();
-    Ok((ids, dists))
+        // Metrics whose distance decomposes into a dot product can be
+        // scored for the whole batch with one GEMM; everything else keeps
+        // the per-pair fallback.
+        let dist_matrix: Option<Array2<f32>> = match self.metric {
+            Distance::Euclidean() => {
+                let q_sq: Vec<f32> = queries.rows().into_iter().map(|r| r.iter().map(|v| v * v).sum()).collect();
+                let dot = queries.dot(&x.t());
+                let mut d = Array2::<f32>::zeros((n, m));
+                for i in 0..n {
+                    for j in 0..m {
+                        let raw = q_sq[i] + x_sq[j] - 2.0 * dot[[i, j]];
+                        d[[i, j]] = raw.max(0.0).sqrt();
+                    }
+                }
+                Some(d)
+            }
+            Distance::Cosine() | Distance::Angular() => {
+                let mut qn = queries.to_owned();
+                for mut row in qn.rows_mut() {
+                    let norm = row.iter().map(|v| v * v).sum::<f32>().sqrt();
+                    if norm > 0.0 {
+                        row.mapv_inplace(|v| v / norm);
+                    }
+                }
+                let mut xn = x.clone();
+                for mut row in xn.rows_mut() {
+                    let norm = row.iter().map(|v| v * v).sum::<f32>().sqrt();
+                    if norm > 0.0 {
+                        row.mapv_inplace(|v| v / norm);
+                    }
+                }
+                let similarity = qn.dot(&xn.t());
+                Some(similarity.mapv(|s| 1.0 - s))
+            }
+            _ => None,
+        };
+
+        // `Custom` has no GEMM decomposition, so it always falls through to
+        // the per-pair fallback below; look the registered function up once
+        // here (rather than once per row) and dispatch through it, matching
+        // `inner_search`'s handling of the same metric instead of the
+        // `f32::INFINITY` placeholder this used to fall back to.
+        let custom_distance_fn = match &self.metric {
+            Distance::Custom(name) => Some(
+                crate::distance_registry::get_distance_function_safe(name)
+                    .map_err(custom_metric_error)?,
+            ),
+            _ => None,
+        };
+
+        let k = k.min(m);
+        let build_row = |i: usize| -> (Vec<i64>, Vec<f32>) {
+            let mut row_results: Vec<(i64, f32)> = if let Some(d) = &dist_matrix {
+                (0..m).map(|j| (ids[j], d[[i, j]])).collect()
+            } else {
+                let q = queries.row(i);
+                let qv = q.to_vec();
+                (0..m)
+                    .map(|j| {
+                        let xv = x.row(j).to_vec();
+                        let dist = match self.metric {
+                            Distance::Manhattan() => crate::metrics::manhattan(&qv, &xv),
+                            Distance::Chebyshev() => crate::metrics::chebyshev(&qv, &xv),
+                            Distance::Minkowski(p) => crate::metrics::minkowski(&qv, &xv, p),
+                            Distance::Hamming() => crate::metrics::hamming(&qv, &xv),
+                            Distance::Jaccard() => crate::metrics::jaccard(&qv, &xv),
+                            Distance::Canberra() => crate::metrics::canberra(&qv, &xv),
+                            Distance::Custom(_) => custom_distance_fn
+                                .as_ref()
+                                .expect("computed above whenever metric is Custom")
+                                .distance(&qv, &xv),
+                            Distance::Euclidean() | Distance::Cosine() | Distance::Angular() => {
+                                unreachable!("decomposable metrics are already handled via GEMM above")
+                            }
+                        };
+                        (ids[j], dist)
+                    })
+                    .collect()
+            };
+
+            if k == 0 {
+                return (Vec::new(), Vec::new());
+            }
+            let (left, mid, _) = row_results.select_nth_unstable_by(k - 1, |a, b| safe_partial_cmp(&a.1, &b.1));
+            let mut top_k = left.to_vec();
+            top_k.push(*mid);
+            top_k.sort_unstable_by(|a, b| safe_partial_cmp(&a.1, &b.1));
+            let row_ids: Vec<i64> = top_k.iter().map(|(id, _)| *id).collect();
+            let row_dists: Vec<f32> = top_k.iter().map(|(_, dist)| *dist).collect();
+            (row_ids, row_dists)
+        };
+
+        // A Python-backed custom metric can't run under `par_iter` without
+        // every row reacquiring the GIL; fall back to a sequential scan for
+        // it, same as `inner_search` — a native registered function (or any
+        // other metric) still parallelizes across rows.
+        let results: Vec<(Vec<i64>, Vec<f32>)> = match &custom_distance_fn {
+            Some(f) if f.is_python() => (0..n).map(build_row).collect(),
+            _ => (0..n).into_par_iter().map(build_row).collect(),
+        };
+
+        Ok(results)
     }
 
-    /// Secure path validation using canonicalization and allowlist
-    /// 
-    /// Replaces the vulnerable simple string check with robust path validation
-    /// that prevents directory traversal attacks through multiple bypass techniques.
-    fn validate_path(path: &str) -> PyResult<()> {
-        // Use the secure path validation module
-        validate_path_secure(path).map(|_| ())
+    /// Write this index to `validated` (`.bin` appended). Takes `&ValidatedPath`
+    /// rather than a bare `&str` so the type system guarantees the path has
+    /// already gone through [`PathAuditor::audit`].
+    fn save_validated(&self, validated: &ValidatedPath) -> PyResult<()> {
+        let full = format!("{}.bin", validated.to_string_lossy());
+        save_index(self, &full).map_err(|e| e.into_pyerr())
+    }
+
+    /// Load an index from `validated` (`.bin` appended).
+    fn load_validated(validated: &ValidatedPath) -> PyResult<Self> {
+        let full = format!("{}.bin", validated.to_string_lossy());
+        load_index(&full).map_err(|e| e.into_pyerr())
     }
 }
 
@@ -873,6 +1235,20 @@ impl AnnBackend for AnnIndex {
 }
 
 
+/// Map a registry lookup failure onto [`RustAnnError`] for callers (like
+/// [`AnnIndex::search_batch_inner`](AnnIndex::search_batch_inner)) that
+/// return it rather than a `PyErr` directly. A captured callback `PyErr` is
+/// preserved via [`RustAnnError::Callback`] instead of being flattened to a
+/// string, same as everywhere else a [`DistanceRegistryError`] crosses this
+/// boundary.
+fn custom_metric_error(e: crate::errors::DistanceRegistryError) -> RustAnnError {
+    use crate::errors::DistanceRegistryError;
+    match e {
+        DistanceRegistryError::PythonCallFailed(err) => RustAnnError::Callback(err),
+        other => RustAnnError::Message(other.to_string()),
+    }
+}
+
 fn safe_partial_cmp(a: &f32, b: &f32) -> std::cmp::Ordering {
     a.partial_cmp(b).unwrap_or_else(|| {
         if a.is_nan() && b.is_nan() {
@@ -886,3 +1262,189 @@ fn safe_partial_cmp(a: &f32, b: &f32) -> std::cmp::Ordering {
         }
     })
 }
+
+/// One candidate in a bounded top-k [`BinaryHeap`]. Ordered by `dist` with
+/// the same NaN-as-greatest convention as [`safe_partial_cmp`], so the
+/// heap's max (the worst candidate) always sits on top.
+#[derive(Debug, Clone, Copy)]
+struct HeapEntry {
+    dist: f32,
+    id: i64,
+}
+
+impl PartialEq for HeapEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.dist == other.dist
+    }
+}
+
+impl Eq for HeapEntry {}
+
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HeapEntry {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        safe_partial_cmp(&self.dist, &other.dist)
+    }
+}
+
+/// Push `entry` into a max-heap bounded to `k` elements: once full, only
+/// displace the current worst (top) candidate if `entry` is strictly
+/// better.
+fn push_bounded(heap: &mut BinaryHeap<HeapEntry>, entry: HeapEntry, k: usize) {
+    if k == 0 {
+        return;
+    }
+    if heap.len() < k {
+        heap.push(entry);
+    } else if let Some(top) = heap.peek() {
+        if entry < *top {
+            heap.pop();
+            heap.push(entry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An `AnnIndex` with `n` entries `0..n`, each a 2D vector `[i, i]`.
+    fn index_with_entries(n: usize) -> AnnIndex {
+        let mut index = AnnIndex::new(2, Distance::Euclidean()).unwrap();
+        for i in 0..n {
+            let v = vec![i as f32, i as f32];
+            let sq = v.iter().map(|x| x * x).sum();
+            index.entries.push(Some((i as i64, v, sq)));
+        }
+        index
+    }
+
+    #[test]
+    fn remove_tombstones_and_preserves_metadata_alignment() {
+        let mut index = index_with_entries(4);
+        let mut schema = HashMap::new();
+        schema.insert("tag".to_string(), MetadataType::String);
+        index.metadata_schema = Some(schema);
+        let mut columns = HashMap::new();
+        columns.insert(
+            "tag".to_string(),
+            vec![
+                MetadataValue::String("a".into()),
+                MetadataValue::String("b".into()),
+                MetadataValue::String("c".into()),
+                MetadataValue::String("d".into()),
+            ],
+        );
+        index.metadata_columns = Some(columns);
+        // 1/4 deleted would exceed the default 0.2 ratio and auto-compact the
+        // tombstone away; raise the threshold so the tombstone stays visible.
+        index.max_deleted_ratio = 1.0;
+
+        index.remove(vec![1]).unwrap();
+
+        assert_eq!(index.deleted_count, 1);
+        assert!(index.entries[1].is_none());
+        // The tombstoned slot's metadata resets to the field type's default...
+        assert_eq!(index.metadata_columns.as_ref().unwrap()["tag"][1], MetadataValue::String(String::new()));
+        // ...while surviving slots keep their original id<->metadata alignment.
+        assert_eq!(index.metadata_columns.as_ref().unwrap()["tag"][2], MetadataValue::String("c".into()));
+        assert_eq!(index.entries[2].as_ref().unwrap().0, 2);
+    }
+
+    #[test]
+    fn compact_drops_tombstones_and_keeps_metadata_aligned_with_ids() {
+        let mut index = index_with_entries(4);
+        let mut columns = HashMap::new();
+        columns.insert(
+            "tag".to_string(),
+            vec![
+                MetadataValue::String("a".into()),
+                MetadataValue::String("b".into()),
+                MetadataValue::String("c".into()),
+                MetadataValue::String("d".into()),
+            ],
+        );
+        index.metadata_columns = Some(columns);
+        index.entries[1] = None;
+        index.entries[3] = None;
+        index.deleted_count = 2;
+
+        index.compact().unwrap();
+
+        assert_eq!(index.deleted_count, 0);
+        assert_eq!(index.entries.len(), 2);
+        let ids: Vec<i64> = index.entries.iter().map(|e| e.as_ref().unwrap().0).collect();
+        assert_eq!(ids, vec![0, 2]);
+        let tags = &index.metadata_columns.as_ref().unwrap()["tag"];
+        assert_eq!(*tags, vec![MetadataValue::String("a".into()), MetadataValue::String("c".into())]);
+    }
+
+    #[test]
+    fn remove_auto_compacts_once_deleted_ratio_exceeds_threshold() {
+        let mut index = index_with_entries(4);
+        index.max_deleted_ratio = 0.2;
+
+        // 1/4 = 0.25 > 0.2, so this single removal should trigger auto-compaction.
+        index.remove(vec![0]).unwrap();
+
+        assert_eq!(index.deleted_count, 0, "compact() should have reset deleted_count");
+        assert_eq!(index.entries.len(), 3, "the tombstoned slot should have been dropped by auto-compaction");
+        let ids: Vec<i64> = index.entries.iter().map(|e| e.as_ref().unwrap().0).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn should_compact_boundary_is_strictly_greater_than() {
+        let mut index = index_with_entries(4);
+        index.max_deleted_ratio = 0.5;
+
+        // 2/4 == 0.5, not strictly greater, so should_compact must stay false.
+        index.deleted_count = 2;
+        assert!(!index.should_compact());
+
+        // 3/4 > 0.5: now it should fire.
+        index.deleted_count = 3;
+        assert!(index.should_compact());
+    }
+
+    /// `inner_search`'s bounded k-element heap must return the same top-k
+    /// (same ids, same order) as a naive collect-everything-then-sort scan,
+    /// for every k from below the dataset size up through above it.
+    #[test]
+    fn bounded_heap_topk_matches_naive_full_sort() {
+        let mut index = AnnIndex::new(3, Distance::Euclidean()).unwrap();
+        for i in 0..50i64 {
+            let v = vec![i as f32, (i * 2) as f32, (i % 7) as f32];
+            let sq = v.iter().map(|x| x * x).sum();
+            index.entries.push(Some((i, v, sq)));
+        }
+        let query = vec![10.0, 5.0, 3.0];
+        let q_sq: f32 = query.iter().map(|x| x * x).sum();
+        let version = index.version.load(AtomicOrdering::Relaxed);
+
+        for k in [1usize, 5, 10, 49, 50, 100] {
+            let (heap_ids, heap_dists) = index.inner_search(&query, q_sq, k, None, version).unwrap();
+
+            let mut naive: Vec<(i64, f32)> = index
+                .entries
+                .iter()
+                .filter_map(|e| e.as_ref())
+                .map(|(id, vec, norm)| (*id, crate::metrics::euclidean_sq(&query, vec, q_sq, *norm)))
+                .collect();
+            naive.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+            naive.truncate(k);
+            let naive_ids: Vec<i64> = naive.iter().map(|(id, _)| *id).collect();
+            let naive_dists: Vec<f32> = naive.iter().map(|(_, d)| *d).collect();
+
+            assert_eq!(heap_ids, naive_ids, "k={k}");
+            for (a, b) in heap_dists.iter().zip(&naive_dists) {
+                assert!((a - b).abs() < 1e-4, "k={k}: {a} vs {b}");
+            }
+        }
+    }
+}
@@ -0,0 +1,531 @@
+//! A small filter-expression language over [`AnnIndex`](crate::index::AnnIndex)'s
+//! metadata columns, used by `search_filtered` / `py_search_filtered`.
+//!
+//! Example predicate: `price > 10.0 AND category = "shoes" AND "red" IN tags`.
+//!
+//! Grammar (lowest to highest precedence):
+//!
+//! ```text
+//! expr    := or_expr
+//! or_expr := and_expr ("OR" and_expr)*
+//! and_expr:= unary ("AND" unary)*
+//! unary   := "NOT" unary | primary
+//! primary := "(" expr ")" | STRING "IN" IDENT | IDENT cmp_op (STRING | NUMBER)
+//! cmp_op  := "=" | "!=" | "<" | "<=" | ">" | ">="
+//! ```
+
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::errors::RustAnnError;
+use crate::index::{MetadataType, MetadataValue};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone)]
+enum Literal {
+    Number(f64),
+    Str(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Number(String),
+    Str(String),
+    Op(&'static str),
+    And,
+    Or,
+    Not,
+    In,
+    LParen,
+    RParen,
+    Eof,
+}
+
+/// A parsed metadata predicate, ready to be evaluated against a candidate
+/// entry's position in the index's metadata columns.
+#[derive(Debug, Clone)]
+pub enum MetadataPredicate {
+    Compare {
+        field: String,
+        op: CompareOp,
+        literal: Literal,
+    },
+    In {
+        field: String,
+        value: String,
+    },
+    And(Box<MetadataPredicate>, Box<MetadataPredicate>),
+    Or(Box<MetadataPredicate>, Box<MetadataPredicate>),
+    Not(Box<MetadataPredicate>),
+}
+
+impl MetadataPredicate {
+    /// Tokenize and parse `source` into a predicate, validating every field
+    /// reference against `schema`. A field absent from `schema` is a parse
+    /// error.
+    pub fn parse(source: &str, schema: &HashMap<String, MetadataType>) -> Result<Self, RustAnnError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, pos: 0, schema };
+        let predicate = parser.parse_expr()?;
+        parser.expect(Token::Eof)?;
+        Ok(predicate)
+    }
+
+    /// Evaluate this predicate against the entry at `idx`, looking its
+    /// fields up in `columns`. A field present in the schema but missing a
+    /// value at this index (short column, or the index was never written)
+    /// evaluates to `false` rather than erroring.
+    pub fn evaluate(&self, idx: usize, columns: &HashMap<String, Vec<MetadataValue>>) -> bool {
+        match self {
+            MetadataPredicate::Compare { field, op, literal } => {
+                match columns.get(field).and_then(|col| col.get(idx)) {
+                    Some(value) => compare(value, *op, literal),
+                    None => false,
+                }
+            }
+            MetadataPredicate::In { field, value } => {
+                match columns.get(field).and_then(|col| col.get(idx)) {
+                    Some(MetadataValue::Tags(tags)) => tags.iter().any(|t| t == value),
+                    _ => false,
+                }
+            }
+            MetadataPredicate::And(lhs, rhs) => lhs.evaluate(idx, columns) && rhs.evaluate(idx, columns),
+            MetadataPredicate::Or(lhs, rhs) => lhs.evaluate(idx, columns) || rhs.evaluate(idx, columns),
+            MetadataPredicate::Not(inner) => !inner.evaluate(idx, columns),
+        }
+    }
+}
+
+fn compare(value: &MetadataValue, op: CompareOp, literal: &Literal) -> bool {
+    match (value, literal) {
+        (MetadataValue::Int(v), Literal::Number(n)) => numeric_cmp(*v as f64, op, *n),
+        (MetadataValue::Float(v), Literal::Number(n)) => numeric_cmp(*v, op, *n),
+        (MetadataValue::Timestamp(v), Literal::Number(n)) => numeric_cmp(*v as f64, op, *n),
+        (MetadataValue::String(v), Literal::Str(s)) => string_cmp(v, op, s),
+        _ => false,
+    }
+}
+
+fn numeric_cmp(lhs: f64, op: CompareOp, rhs: f64) -> bool {
+    match op {
+        CompareOp::Eq => lhs == rhs,
+        CompareOp::Ne => lhs != rhs,
+        CompareOp::Lt => lhs < rhs,
+        CompareOp::Le => lhs <= rhs,
+        CompareOp::Gt => lhs > rhs,
+        CompareOp::Ge => lhs >= rhs,
+    }
+}
+
+/// String ordering is lexicographic, which mainly exists to support
+/// prefix-range queries, e.g. `name >= "a" AND name < "b"`.
+fn string_cmp(lhs: &str, op: CompareOp, rhs: &str) -> bool {
+    match op {
+        CompareOp::Eq => lhs == rhs,
+        CompareOp::Ne => lhs != rhs,
+        CompareOp::Lt => lhs < rhs,
+        CompareOp::Le => lhs <= rhs,
+        CompareOp::Gt => lhs > rhs,
+        CompareOp::Ge => lhs >= rhs,
+    }
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    schema: &'a HashMap<String, MetadataType>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> &Token {
+        self.tokens.get(self.pos).unwrap_or(&Token::Eof)
+    }
+
+    fn advance(&mut self) -> Token {
+        let tok = self.tokens.get(self.pos).cloned().unwrap_or(Token::Eof);
+        self.pos += 1;
+        tok
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), RustAnnError> {
+        let tok = self.advance();
+        if tok == expected {
+            Ok(())
+        } else {
+            Err(RustAnnError::Message(format!(
+                "predicate parse error: expected {:?}, found {:?}",
+                expected, tok
+            )))
+        }
+    }
+
+    fn parse_expr(&mut self) -> Result<MetadataPredicate, RustAnnError> {
+        self.parse_or()
+    }
+
+    fn parse_or(&mut self) -> Result<MetadataPredicate, RustAnnError> {
+        let mut lhs = self.parse_and()?;
+        while *self.peek() == Token::Or {
+            self.advance();
+            let rhs = self.parse_and()?;
+            lhs = MetadataPredicate::Or(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_and(&mut self) -> Result<MetadataPredicate, RustAnnError> {
+        let mut lhs = self.parse_unary()?;
+        while *self.peek() == Token::And {
+            self.advance();
+            let rhs = self.parse_unary()?;
+            lhs = MetadataPredicate::And(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_unary(&mut self) -> Result<MetadataPredicate, RustAnnError> {
+        if *self.peek() == Token::Not {
+            self.advance();
+            let inner = self.parse_unary()?;
+            return Ok(MetadataPredicate::Not(Box::new(inner)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<MetadataPredicate, RustAnnError> {
+        match self.advance() {
+            Token::LParen => {
+                let inner = self.parse_expr()?;
+                self.expect(Token::RParen)?;
+                Ok(inner)
+            }
+            Token::Str(value) => {
+                self.expect(Token::In)?;
+                let field = self.expect_ident()?;
+                self.check_field(&field, MetadataType::Tags)?;
+                Ok(MetadataPredicate::In { field, value })
+            }
+            Token::Ident(field) => {
+                let op = self.expect_cmp_op()?;
+                let literal = self.expect_literal()?;
+                self.check_field(&field, field_type_for(&literal))?;
+                Ok(MetadataPredicate::Compare { field, op, literal })
+            }
+            other => Err(RustAnnError::Message(format!(
+                "predicate parse error: unexpected token {:?}",
+                other
+            ))),
+        }
+    }
+
+    fn expect_ident(&mut self) -> Result<String, RustAnnError> {
+        match self.advance() {
+            Token::Ident(name) => Ok(name),
+            other => Err(RustAnnError::Message(format!(
+                "predicate parse error: expected field name, found {:?}",
+                other
+            ))),
+        }
+    }
+
+    fn expect_cmp_op(&mut self) -> Result<CompareOp, RustAnnError> {
+        match self.advance() {
+            Token::Op("=") => Ok(CompareOp::Eq),
+            Token::Op("!=") => Ok(CompareOp::Ne),
+            Token::Op("<") => Ok(CompareOp::Lt),
+            Token::Op("<=") => Ok(CompareOp::Le),
+            Token::Op(">") => Ok(CompareOp::Gt),
+            Token::Op(">=") => Ok(CompareOp::Ge),
+            other => Err(RustAnnError::Message(format!(
+                "predicate parse error: expected a comparison operator, found {:?}",
+                other
+            ))),
+        }
+    }
+
+    fn expect_literal(&mut self) -> Result<Literal, RustAnnError> {
+        match self.advance() {
+            Token::Number(text) => text.parse::<f64>().map(Literal::Number).map_err(|_| {
+                RustAnnError::Message(format!("predicate parse error: invalid number '{}'", text))
+            }),
+            Token::Str(value) => Ok(Literal::Str(value)),
+            other => Err(RustAnnError::Message(format!(
+                "predicate parse error: expected a literal value, found {:?}",
+                other
+            ))),
+        }
+    }
+
+    /// Reference to a field absent from the schema is a parse error. For an
+    /// `IN` clause the field must also be typed as `Tags` in the schema.
+    fn check_field(&self, field: &str, expected: MetadataType) -> Result<(), RustAnnError> {
+        match self.schema.get(field) {
+            None => Err(RustAnnError::Message(format!(
+                "predicate parse error: unknown metadata field '{}'",
+                field
+            ))),
+            Some(MetadataType::Tags) if expected == MetadataType::Tags => Ok(()),
+            Some(_) if expected == MetadataType::Tags => Err(RustAnnError::Message(format!(
+                "predicate parse error: field '{}' is not a Tags field, cannot use IN",
+                field
+            ))),
+            Some(_) => Ok(()),
+        }
+    }
+}
+
+fn field_type_for(literal: &Literal) -> MetadataType {
+    match literal {
+        Literal::Number(_) => MetadataType::Float,
+        Literal::Str(_) => MetadataType::String,
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, RustAnnError> {
+    let mut chars = source.chars().peekable();
+    let mut tokens = Vec::new();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        match c {
+            '(' => {
+                chars.next();
+                tokens.push(Token::LParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RParen);
+            }
+            '"' | '\'' => {
+                tokens.push(Token::Str(read_string(&mut chars, c)?));
+            }
+            '=' => {
+                chars.next();
+                tokens.push(Token::Op("="));
+            }
+            '!' => {
+                chars.next();
+                expect_char(&mut chars, '=')?;
+                tokens.push(Token::Op("!="));
+            }
+            '<' => {
+                chars.next();
+                if chars.peek() == Some(&'=') {
+                    chars.next();
+                    tokens.push(Token::Op("<="));
+                } else {
+                    tokens.push(Token::Op("<"));
+                }
+            }
+            '>' => {
+                chars.next();
+                if chars.peek() == Some(&'=') {
+                    chars.next();
+                    tokens.push(Token::Op(">="));
+                } else {
+                    tokens.push(Token::Op(">"));
+                }
+            }
+            c if c.is_ascii_digit() || (c == '-' && tokens_allow_unary_minus(&tokens)) => {
+                tokens.push(Token::Number(read_number(&mut chars)));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let word = read_word(&mut chars);
+                tokens.push(match word.to_ascii_uppercase().as_str() {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    "NOT" => Token::Not,
+                    "IN" => Token::In,
+                    _ => Token::Ident(word),
+                });
+            }
+            other => {
+                return Err(RustAnnError::Message(format!(
+                    "predicate parse error: unexpected character '{}'",
+                    other
+                )))
+            }
+        }
+    }
+
+    tokens.push(Token::Eof);
+    Ok(tokens)
+}
+
+fn tokens_allow_unary_minus(tokens: &[Token]) -> bool {
+    !matches!(tokens.last(), Some(Token::Number(_)) | Some(Token::Ident(_)))
+}
+
+fn expect_char(chars: &mut Peekable<Chars>, expected: char) -> Result<(), RustAnnError> {
+    match chars.next() {
+        Some(c) if c == expected => Ok(()),
+        other => Err(RustAnnError::Message(format!(
+            "predicate parse error: expected '{}', found {:?}",
+            expected, other
+        ))),
+    }
+}
+
+fn read_string(chars: &mut Peekable<Chars>, quote: char) -> Result<String, RustAnnError> {
+    chars.next(); // consume opening quote
+    let mut value = String::new();
+    loop {
+        match chars.next() {
+            Some(c) if c == quote => return Ok(value),
+            Some(c) => value.push(c),
+            None => {
+                return Err(RustAnnError::Message(
+                    "predicate parse error: unterminated string literal".to_string(),
+                ))
+            }
+        }
+    }
+}
+
+fn read_number(chars: &mut Peekable<Chars>) -> String {
+    let mut text = String::new();
+    if chars.peek() == Some(&'-') {
+        text.push(chars.next().unwrap());
+    }
+    while let Some(&c) = chars.peek() {
+        if c.is_ascii_digit() || c == '.' {
+            text.push(c);
+            chars.next();
+        } else {
+            break;
+        }
+    }
+    text
+}
+
+fn read_word(chars: &mut Peekable<Chars>) -> String {
+    let mut word = String::new();
+    while let Some(&c) = chars.peek() {
+        if c.is_alphanumeric() || c == '_' {
+            word.push(c);
+            chars.next();
+        } else {
+            break;
+        }
+    }
+    word
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> HashMap<String, MetadataType> {
+        let mut s = HashMap::new();
+        s.insert("price".to_string(), MetadataType::Float);
+        s.insert("category".to_string(), MetadataType::String);
+        s.insert("tags".to_string(), MetadataType::Tags);
+        s.insert("qty".to_string(), MetadataType::Int);
+        s
+    }
+
+    /// idx 0: price=150, category="shoes", qty=10, tags=["red"]
+    /// idx 1: price=5,   category="hats",  qty=-3, tags=["blue"]
+    fn columns() -> HashMap<String, Vec<MetadataValue>> {
+        let mut c = HashMap::new();
+        c.insert("price".to_string(), vec![MetadataValue::Float(150.0), MetadataValue::Float(5.0)]);
+        c.insert(
+            "category".to_string(),
+            vec![MetadataValue::String("shoes".to_string()), MetadataValue::String("hats".to_string())],
+        );
+        c.insert("qty".to_string(), vec![MetadataValue::Int(10), MetadataValue::Int(-3)]);
+        c.insert(
+            "tags".to_string(),
+            vec![MetadataValue::Tags(vec!["red".to_string()]), MetadataValue::Tags(vec!["blue".to_string()])],
+        );
+        c
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let schema = schema();
+        let cols = columns();
+        // At idx 0: price>100.0 is true (A), category="shoes" is true (B),
+        // qty<0 is false (C). `A OR B AND C` must parse as `A OR (B AND C)`
+        // = true OR (true AND false) = true — if OR bound tighter instead
+        // (`(A OR B) AND C`) this would be false, so the two groupings
+        // disagree here and the test actually exercises precedence.
+        let predicate = MetadataPredicate::parse("price > 100.0 OR category = \"shoes\" AND qty < 0", &schema).unwrap();
+        assert!(predicate.evaluate(0, &cols));
+    }
+
+    #[test]
+    fn parentheses_override_default_precedence() {
+        let schema = schema();
+        let cols = columns();
+        // Same idx 0 values as above, but explicit parens now force the OR
+        // to combine first: `(A OR B) AND C` = (true OR true) AND false = false.
+        let predicate =
+            MetadataPredicate::parse("(price > 100.0 OR category = \"shoes\") AND qty < 0", &schema).unwrap();
+        assert!(!predicate.evaluate(0, &cols));
+    }
+
+    #[test]
+    fn in_checks_membership_in_a_tags_column() {
+        let schema = schema();
+        let cols = columns();
+        let predicate = MetadataPredicate::parse("\"red\" IN tags", &schema).unwrap();
+        assert!(predicate.evaluate(0, &cols));
+        assert!(!predicate.evaluate(1, &cols));
+    }
+
+    #[test]
+    fn not_negates_the_inner_predicate() {
+        let schema = schema();
+        let cols = columns();
+        let predicate = MetadataPredicate::parse("NOT (category = \"shoes\")", &schema).unwrap();
+        assert!(!predicate.evaluate(0, &cols));
+        assert!(predicate.evaluate(1, &cols));
+    }
+
+    #[test]
+    fn unary_minus_is_lexed_as_part_of_a_negative_number() {
+        let schema = schema();
+        let cols = columns();
+        let predicate = MetadataPredicate::parse("qty < -1", &schema).unwrap();
+        assert!(!predicate.evaluate(0, &cols)); // qty = 10
+        assert!(predicate.evaluate(1, &cols)); // qty = -3 < -1
+
+        // Directly check the lexer too: a `-` right after an operator token
+        // starts a number, not a separate token.
+        let tokens = tokenize("qty < -1").unwrap();
+        assert_eq!(tokens, vec![Token::Ident("qty".to_string()), Token::Op("<"), Token::Number("-1".to_string()), Token::Eof]);
+    }
+
+    #[test]
+    fn unterminated_string_literal_is_a_parse_error() {
+        let err = MetadataPredicate::parse("category = \"shoes", &schema()).unwrap_err();
+        assert!(err.to_string().contains("unterminated string literal"));
+    }
+
+    #[test]
+    fn unknown_field_is_a_parse_error() {
+        let err = MetadataPredicate::parse("bogus = 1", &schema()).unwrap_err();
+        assert!(err.to_string().contains("unknown metadata field"));
+    }
+
+    #[test]
+    fn in_on_a_non_tags_field_is_a_type_mismatch_error() {
+        let err = MetadataPredicate::parse("\"red\" IN category", &schema()).unwrap_err();
+        assert!(err.to_string().contains("not a Tags field"));
+    }
+}
@@ -1,12 +1,20 @@
 // src/path_validation.rs
 //! Secure path validation module to prevent directory traversal attacks
 //!
-//! This module provides robust path validation using canonicalization
-//! and allowlist-based directory restrictions.
+//! This module resolves paths *lexically* (no filesystem access, so it
+//! works for not-yet-existing nested paths), audits the result component
+//! by component with a [`PathAuditor`], and then checks it against an
+//! allowlist of permitted base directories.
 
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
 use crate::errors::RustAnnError;
-use pyo3::PyResult;
+use pyo3::{PyErr, PyResult};
+
+#[cfg(unix)]
+use std::os::unix::ffi::OsStrExt;
 
 /// Configuration for allowed base directories
 static ALLOWED_BASE_DIRS: &[&str] = &[
@@ -18,19 +26,22 @@ static ALLOWED_BASE_DIRS: &[&str] = &[
 ];
 
 /// Validates a file path to prevent directory traversal attacks
-/// 
-/// Uses std::path::Path::canonicalize() for robust path resolution
-/// and enforces an allowlist of permitted base directories.
-/// 
+///
+/// Normalizes percent-encoding, resolves the path lexically (so it works
+/// even for nested paths that don't exist yet), audits it component by
+/// component with the shared [`PathAuditor`], and enforces an allowlist of
+/// permitted base directories.
+///
 /// # Arguments
 /// * `path` - The path to validate
-/// 
+///
 /// # Returns
-/// * `PyResult<PathBuf>` - Canonicalized safe path or error
-/// 
+/// * `PyResult<PathBuf>` - Resolved safe path or error
+///
 /// # Security Features
-/// - Resolves all symbolic links and relative components
-/// - Prevents traversal outside allowed directories  
+/// - Rejects traversal/absolute components and reserved device names
+/// - Refuses symlinked intermediate directories
+/// - Prevents traversal outside allowed directories
 /// - Handles URL encoding, double encoding, mixed separators
 /// - Validates against null bytes and control characters
 /// 
@@ -47,29 +58,100 @@ static ALLOWED_BASE_DIRS: &[&str] = &[
 /// assert!(validate_path_secure("/etc/passwd").is_err());
 /// ```
 pub fn validate_path_secure(path: &str) -> PyResult<PathBuf> {
-    // Check for null bytes and control characters
-    if path.contains('\0') || path.chars().any(|c| c.is_control() && c != '\t' && c != '\n' && c != '\r') {
+    Ok(ValidatedPath::try_from(path)?.into_path_buf())
+}
+
+/// Process-wide sandbox root [`validate_path_secure_in_sandbox`] resolves
+/// against when no explicit base is given. Defaults to the current working
+/// directory at first use; override with [`set_sandbox_root`].
+fn sandbox_root_cell() -> &'static Mutex<PathBuf> {
+    static ROOT: OnceLock<Mutex<PathBuf>> = OnceLock::new();
+    ROOT.get_or_init(|| Mutex::new(std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))))
+}
+
+/// The current process-wide sandbox root. See [`set_sandbox_root`].
+pub fn sandbox_root() -> PathBuf {
+    sandbox_root_cell().lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Reconfigure the process-wide sandbox root used by
+/// [`validate_path_secure_in_sandbox`], e.g. to point persistence at a
+/// designated data directory instead of only the current working directory.
+/// `root` must already exist: it's canonicalized here, and
+/// [`validate_path_within`] canonicalizes it again on each call.
+pub fn set_sandbox_root(root: impl AsRef<Path>) -> PyResult<()> {
+    let canonical = std::fs::canonicalize(root.as_ref())
+        .map_err(|e| RustAnnError::py_err("InvalidPath", format!("Cannot resolve sandbox root: {}", e)))?;
+    *sandbox_root_cell().lock().unwrap_or_else(|e| e.into_inner()) = canonical;
+    Ok(())
+}
+
+/// Validate `candidate` against the process-wide [`sandbox_root`] rather
+/// than the fixed [`ALLOWED_BASE_DIRS`] allowlist `validate_path_secure`
+/// uses. Useful once a caller has pointed the sandbox root at a designated
+/// directory via [`set_sandbox_root`] and wants every subsequent path
+/// validated against it without naming it again.
+pub fn validate_path_secure_in_sandbox(candidate: &str) -> PyResult<PathBuf> {
+    validate_path_within(&sandbox_root(), candidate)
+}
+
+/// Validate `candidate` against an explicit `base` by canonicalizing both
+/// and asserting the resolved base is a prefix of the resolved
+/// candidate's directory, rather than checking `candidate` against the fixed
+/// [`ALLOWED_BASE_DIRS`] allowlist. `canonicalize` already collapses `..`
+/// and follows symlinks, so escaping `base` is caught the same way
+/// [`PathAuditor`] catches it for the fixed allowlist — this just lets a
+/// caller designate any existing directory as the root instead of only the
+/// hardcoded ones. `candidate`'s leaf component is allowed not to exist yet
+/// (only its parent directory is canonicalized), so this still works for a
+/// not-yet-written save target like `./data/model.bin`.
+pub fn validate_path_within(base: &Path, candidate: &str) -> PyResult<PathBuf> {
+    check_no_control_bytes(candidate)?;
+    let normalized = decode_percent_encoding(candidate)?;
+    let normalized_seps = normalized.replace('\\', "/");
+
+    let canonical_base = std::fs::canonicalize(base)
+        .map_err(|e| RustAnnError::py_err("InvalidPath", format!("Cannot resolve sandbox root: {}", e)))?;
+
+    let joined = lexically_resolve(&canonical_base, Path::new(&normalized_seps))?;
+    let (parent, file_name) = match (joined.parent(), joined.file_name()) {
+        (Some(parent), Some(file_name)) => (parent, file_name),
+        _ => return Err(RustAnnError::py_err("InvalidPath", "Path has no file name")),
+    };
+
+    if is_reserved_windows_name(file_name) {
         return Err(RustAnnError::py_err(
-            "InvalidPath", 
-            "Path contains invalid characters"
+            "InvalidPath",
+            format!("'{}' is a reserved device name", file_name.to_string_lossy()),
         ));
     }
 
-    // Helper: identify obviously malicious patterns
-    fn contains_dangerous_sequences(s: &str) -> bool {
-        let dangerous_patterns = [
-            "..", "/etc/", "\\windows\\", "c:\\", "proc/", "dev/",
-            // Encoded traversal attempts (single/double encodings)
-            "%2e%2e", "%2f", "%5c", "..%2f", "..\\", ".%2e",
-            "%252e", "%252f", "%255c",
-            // Guard for deeper encodings observed in tests and common payloads
-            "%25252e", "%25252f", "%25255c"
-        ];
-        let lower = s.to_lowercase();
-        dangerous_patterns.iter().any(|p| lower.contains(p))
+    let canonical_parent = std::fs::canonicalize(parent)
+        .map_err(|e| RustAnnError::py_err("InvalidPath", format!("Cannot resolve path: {}", e)))?;
+
+    if !canonical_parent.starts_with(&canonical_base) {
+        return Err(RustAnnError::py_err("InvalidPath", "Path escapes the sandbox root"));
+    }
+
+    Ok(canonical_parent.join(file_name))
+}
+
+/// Check for null bytes and control characters that have no business in a
+/// filesystem path.
+fn check_no_control_bytes(path: &str) -> PyResult<()> {
+    if path.contains('\0') || path.chars().any(|c| c.is_control() && c != '\t' && c != '\n' && c != '\r') {
+        return Err(RustAnnError::py_err(
+            "InvalidPath",
+            "Path contains invalid characters"
+        ));
     }
+    Ok(())
+}
 
-    // Helper: percent-decode a string; returns None on malformed encodings
+/// Percent-decode `input` up to a small, safe limit, to catch double/triple
+/// encodings. Returns the fully-decoded string, or an error on malformed
+/// encoding.
+fn decode_percent_encoding(input: &str) -> PyResult<String> {
     fn percent_decode_once(input: &str) -> Option<String> {
         let bytes = input.as_bytes();
         let mut out: Vec<u8> = Vec::with_capacity(bytes.len());
@@ -93,133 +175,455 @@ pub fn validate_path_secure(path: &str) -> PyResult<PathBuf> {
         String::from_utf8(out).ok()
     }
 
-    // Check original and iteratively percent-decoded forms for dangerous sequences
-    if contains_dangerous_sequences(path) {
-        return Err(RustAnnError::py_err(
-            "InvalidPath",
-            "Path contains potentially dangerous sequences",
-        ));
-    }
-
-    // Decode up to a small, safe limit to catch double/triple encodings
-    let mut cur = path.to_string();
+    let mut cur = input.to_string();
     for _ in 0..4 {
-        if let Some(decoded) = percent_decode_once(&cur) {
-            if decoded == cur { break; }
-            if contains_dangerous_sequences(&decoded) {
+        match percent_decode_once(&cur) {
+            Some(decoded) if decoded == cur => break,
+            Some(decoded) => cur = decoded,
+            None => {
+                // Malformed percent-encoding — reject to be safe
                 return Err(RustAnnError::py_err(
                     "InvalidPath",
-                    "Path contains potentially dangerous sequences after decoding",
+                    "Path contains malformed percent-encoding",
                 ));
             }
-            cur = decoded;
-        } else {
-            // Malformed percent-encoding — reject to be safe
+        }
+    }
+    Ok(cur)
+}
+
+/// The crate-wide [`PathAuditor`], shared so its audited-prefix cache
+/// actually saves repeated `lstat` calls across successive `save`/`load`
+/// calls rather than being rebuilt (and discarded) on every path.
+fn shared_auditor() -> &'static PathAuditor {
+    static AUDITOR: OnceLock<PathAuditor> = OnceLock::new();
+    AUDITOR.get_or_init(PathAuditor::new)
+}
+
+/// A filesystem path guaranteed to be absolute. This is the building block
+/// [`ValidatedPath`] is constructed from; on its own it makes no claim
+/// about traversal-safety or living inside an allowed directory.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AbsoluteSystemPath(PathBuf);
+
+impl AbsoluteSystemPath {
+    pub fn as_path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl TryFrom<PathBuf> for AbsoluteSystemPath {
+    type Error = PyErr;
+
+    fn try_from(path: PathBuf) -> PyResult<Self> {
+        if !path.is_absolute() {
+            return Err(RustAnnError::py_err("InvalidPath", "Path is not absolute"));
+        }
+        Ok(Self(path))
+    }
+}
+
+/// A path that has passed [`PathAuditor::audit`] — traversal/absolute
+/// components, reserved device names, symlinked intermediates, and the
+/// base-dir allowlist have all already been checked. This is the only way
+/// to construct one: every filesystem touch in the crate that takes
+/// `&ValidatedPath` rather than a bare `&str`/`Path` is guaranteed, by the
+/// type system, to have gone through validation first.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ValidatedPath(AbsoluteSystemPath);
+
+impl ValidatedPath {
+    pub fn as_path(&self) -> &Path {
+        self.0.as_path()
+    }
+
+    pub fn to_string_lossy(&self) -> std::borrow::Cow<'_, str> {
+        self.as_path().to_string_lossy()
+    }
+
+    fn into_path_buf(self) -> PathBuf {
+        self.0 .0
+    }
+
+    /// This path expressed relative to whichever allowed base directory it
+    /// resolved under, for contexts that want a portable, base-independent
+    /// identifier rather than an absolute filesystem path. `None` only if
+    /// the allowlist changed out from under an already-validated path.
+    pub fn anchored(&self) -> Option<AnchoredPath> {
+        let current_dir = std::env::current_dir().ok()?;
+        for &allowed_dir in ALLOWED_BASE_DIRS {
+            let base = lexically_resolve(&current_dir, Path::new(allowed_dir)).ok()?;
+            if let Ok(relative) = self.as_path().strip_prefix(&base) {
+                return Some(AnchoredPath {
+                    base: allowed_dir,
+                    relative: relative.to_path_buf(),
+                });
+            }
+        }
+        None
+    }
+}
+
+impl TryFrom<&str> for ValidatedPath {
+    type Error = PyErr;
+
+    fn try_from(path: &str) -> PyResult<Self> {
+        check_no_control_bytes(path)?;
+        // Normalize percent-encoding up front, so the auditor always sees
+        // the real components it's judging rather than an encoded
+        // approximation of them (e.g. `%2e%2e` must become `..` *before*
+        // component analysis, or it'd be treated as an oddly-named but
+        // harmless `Normal` segment).
+        let normalized = decode_percent_encoding(path)?;
+        let resolved = shared_auditor().audit(&normalized)?;
+        Ok(Self(AbsoluteSystemPath::try_from(resolved)?))
+    }
+}
+
+impl TryFrom<&Path> for ValidatedPath {
+    type Error = PyErr;
+
+    fn try_from(path: &Path) -> PyResult<Self> {
+        Self::try_from(path.to_string_lossy().as_ref())
+    }
+}
+
+/// Build a `ValidatedPath` straight from raw filesystem bytes (e.g. Python
+/// `os.fsencode()` output), for paths that aren't valid UTF-8 — common
+/// enough on Linux that routing them through a `&str` would silently
+/// exclude them or mangle them via a lossy conversion. No percent-decoding
+/// is applied here: these are already real path bytes, not a user-typed or
+/// URL-style string, so decoding them would be a re-interpretation rather
+/// than a normalization. The null-byte/control-character and component
+/// audits still run on the bytes as given.
+#[cfg(unix)]
+impl TryFrom<&[u8]> for ValidatedPath {
+    type Error = PyErr;
+
+    fn try_from(bytes: &[u8]) -> PyResult<Self> {
+        check_no_control_bytes_raw(bytes)?;
+        let os_path = OsStr::from_bytes(bytes);
+        let resolved = shared_auditor().audit_os(os_path)?;
+        Ok(Self(AbsoluteSystemPath::try_from(resolved)?))
+    }
+}
+
+/// Byte-level equivalent of [`check_no_control_bytes`], for input that
+/// hasn't been (and shouldn't be) decoded to `str` first.
+#[cfg(unix)]
+fn check_no_control_bytes_raw(bytes: &[u8]) -> PyResult<()> {
+    let is_disallowed = |b: u8| b == 0 || (b < 0x20 && b != b'\t' && b != b'\n' && b != b'\r');
+    if bytes.iter().copied().any(is_disallowed) {
+        return Err(RustAnnError::py_err(
+            "InvalidPath",
+            "Path contains invalid characters",
+        ));
+    }
+    Ok(())
+}
+
+/// A [`ValidatedPath`] expressed relative to the allowed base directory it
+/// resolved under (e.g. base `./models`, relative `2024/run/index.bin`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AnchoredPath {
+    base: &'static str,
+    relative: PathBuf,
+}
+
+impl AnchoredPath {
+    pub fn base(&self) -> &'static str {
+        self.base
+    }
+
+    pub fn relative(&self) -> &Path {
+        &self.relative
+    }
+}
+
+/// Component-by-component path auditor, modeled on Mercurial's `hg-core`
+/// path auditor: rather than scanning the raw string for dangerous
+/// substrings (which both over-rejects legitimate names like
+/// `my..model.bin` and under-rejects encodings nobody thought to
+/// enumerate), it walks the *resolved* path one [`Component`] at a time.
+///
+/// For every prefix from root to leaf it rejects traversal/absolute
+/// components, rejects Windows reserved device names, and `lstat`s each
+/// intermediate directory to refuse a symlink that would otherwise redirect
+/// the write outside the allowed root. Already-audited directory prefixes
+/// are cached, so repeated saves into the same directory only `lstat` the
+/// chain once.
+pub struct PathAuditor {
+    audited_dirs: Mutex<HashSet<PathBuf>>,
+}
+
+impl Default for PathAuditor {
+    fn default() -> Self {
+        Self {
+            audited_dirs: Mutex::new(HashSet::new()),
+        }
+    }
+}
+
+impl PathAuditor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolve and audit `path`, returning the resolved, allowlisted form.
+    /// `path` should already have any percent-encoding normalized away.
+    pub fn audit(&self, path: &str) -> PyResult<PathBuf> {
+        // Treat `\` as a separator too, so a path carrying Windows-style
+        // separators still decomposes into real components instead of one
+        // odd-looking `Normal` segment that a component audit would wave
+        // through.
+        let normalized_seps = path.replace('\\', "/");
+
+        // Resolve `~`, `.`, and `..` lexically (no filesystem access), so
+        // this works just as well for deeply nested paths that don't exist
+        // yet as it does for existing ones. Any `..`/absolute component
+        // that would escape the anchor is rejected here.
+        let resolved = expand_path(&normalized_seps)?;
+
+        self.audit_components(&resolved)?;
+
+        if !is_path_in_allowed_dirs(&resolved) {
             return Err(RustAnnError::py_err(
                 "InvalidPath",
-                "Path contains malformed percent-encoding",
+                "Path is outside allowed directories",
             ));
         }
+
+        Ok(resolved)
     }
 
-    // Convert to Path and validate
-    let path_buf = PathBuf::from(path);
-    
-    // Check for absolute paths (security risk)
-    if path_buf.is_absolute() {
-        return Err(RustAnnError::py_err(
-            "InvalidPath", 
-            "Absolute paths are not allowed"
-        ));
+    /// Byte-level equivalent of [`PathAuditor::audit`]: resolves and audits
+    /// a path built directly from raw filesystem bytes, with no
+    /// percent-decoding or separator normalization — non-UTF-8 paths on
+    /// Unix have no `\`-separator ambiguity to normalize.
+    #[cfg(unix)]
+    pub fn audit_os(&self, path: &OsStr) -> PyResult<PathBuf> {
+        let resolved = expand_path_os(path)?;
+        self.audit_components(&resolved)?;
+
+        if !is_path_in_allowed_dirs(&resolved) {
+            return Err(RustAnnError::py_err(
+                "InvalidPath",
+                "Path is outside allowed directories",
+            ));
+        }
+
+        Ok(resolved)
     }
 
-    // Try to canonicalize the path
-    // Note: canonicalize() requires the path to exist, so we need a different approach
-    // for paths that don't exist yet (like when saving new files)
-    let current_dir = std::env::current_dir()
-        .map_err(|e| RustAnnError::py_err("IOError", format!("Cannot get current directory: {}", e)))?;
-    
-    let full_path = current_dir.join(&path_buf);
-    
-    // Resolve parent directory if path doesn't exist
-    let (resolved_path, filename) = if full_path.exists() {
-        (full_path.canonicalize()
-            .map_err(|e| RustAnnError::py_err("InvalidPath", format!("Cannot resolve path: {}", e)))?, 
-         None)
+    /// Walk `resolved` from root to leaf, rejecting reserved device names at
+    /// every segment and `lstat`-ing each intermediate directory prefix to
+    /// make sure it isn't a symlink. The final (leaf) component is the file
+    /// being written and is checked for a reserved name but not `lstat`'d,
+    /// since it's expected not to exist yet.
+    fn audit_components(&self, resolved: &Path) -> PyResult<()> {
+        let components: Vec<Component> = resolved.components().collect();
+        let mut prefix = PathBuf::new();
+        let mut cache = self.audited_dirs.lock().unwrap_or_else(|e| e.into_inner());
+
+        for (i, component) in components.iter().enumerate() {
+            match component {
+                Component::Normal(segment) => {
+                    if is_reserved_windows_name(segment) {
+                        return Err(RustAnnError::py_err(
+                            "InvalidPath",
+                            format!("'{}' is a reserved device name", segment.to_string_lossy()),
+                        ));
+                    }
+                }
+                Component::ParentDir | Component::RootDir | Component::Prefix(_) if i != 0 => {
+                    // `resolved` comes out of `expand_path`, which already
+                    // resolves the user-supplied part lexically against its
+                    // anchor, so a traversal/absolute component should only
+                    // ever appear here as the anchor's own leading root —
+                    // anywhere else means something upstream let it through.
+                    return Err(RustAnnError::py_err(
+                        "InvalidPath",
+                        "Path contains an unresolved traversal or absolute component",
+                    ));
+                }
+                _ => {}
+            }
+
+            prefix.push(component.as_os_str());
+
+            let is_leaf = i == components.len() - 1;
+            if !is_leaf && component_is_normal(component) && !cache.contains(&prefix) {
+                if let Ok(meta) = std::fs::symlink_metadata(&prefix) {
+                    if meta.file_type().is_symlink() {
+                        return Err(RustAnnError::py_err(
+                            "InvalidPath",
+                            format!("'{}' is a symlink", prefix.display()),
+                        ));
+                    }
+                }
+                cache.insert(prefix.clone());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn component_is_normal(component: &Component) -> bool {
+    matches!(component, Component::Normal(_))
+}
+
+/// Case-insensitive check for the reserved Windows device names (`CON`,
+/// `PRN`, `AUX`, `NUL`, `COM1`-`COM9`, `LPT1`-`LPT9`). These are refused
+/// regardless of extension, since Windows reserves the whole stem (e.g.
+/// `con.txt` is just as reserved as `con`).
+fn is_reserved_windows_name(segment: &OsStr) -> bool {
+    let name = segment.to_string_lossy();
+    let stem = name.split('.').next().unwrap_or(&name);
+    matches!(
+        stem.to_ascii_uppercase().as_str(),
+        "CON" | "PRN" | "AUX" | "NUL"
+            | "COM1" | "COM2" | "COM3" | "COM4" | "COM5" | "COM6" | "COM7" | "COM8" | "COM9"
+            | "LPT1" | "LPT2" | "LPT3" | "LPT4" | "LPT5" | "LPT6" | "LPT7" | "LPT8" | "LPT9"
+    )
+}
+
+/// Whether `segment` looks like a Windows drive letter (`C:`, `d:`, ...).
+fn is_windows_drive_segment(segment: &OsStr) -> bool {
+    let s = segment.to_string_lossy();
+    let bytes = s.as_bytes();
+    bytes.len() == 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':'
+}
+
+/// Expand a leading `~` to the user's home directory, then resolve `.` and
+/// `..` against the appropriate anchor (the home directory for `~` paths,
+/// the current directory otherwise) purely lexically — no `canonicalize()`,
+/// so it works for paths whose parent chain doesn't exist yet. `..` is only
+/// rejected if it would pop back out past that anchor; legitimate nested
+/// targets like `./models/2024/run/index.bin` resolve without touching disk.
+fn expand_path(path: &str) -> PyResult<PathBuf> {
+    if let Some(rest) = path.strip_prefix('~') {
+        let home = home_dir()?;
+        let rest = rest.strip_prefix('/').unwrap_or(rest);
+        lexically_resolve(&home, Path::new(rest))
     } else {
-        // For non-existent files, canonicalize the parent directory
-        let parent = full_path.parent()
-            .ok_or_else(|| RustAnnError::py_err("InvalidPath", "Invalid parent directory"))?;
-        
-        // Create parent directory if it doesn't exist (but only if it's safe)
-        if !parent.exists() {
-            let parent_str = parent.to_string_lossy();
-            if !is_path_in_allowed_dirs(&parent_str) {
+        let path_buf = Path::new(path);
+        if path_buf.is_absolute() {
+            return Err(RustAnnError::py_err(
+                "InvalidPath",
+                "Absolute paths are not allowed",
+            ));
+        }
+        // `Path::is_absolute()` only recognizes drive-letter prefixes
+        // (`Component::Prefix`) when actually compiled for Windows; reject
+        // them by name here too so a path like `C:\Windows\System32` is
+        // refused on every host platform, not just the one it targets.
+        if let Some(Component::Normal(first)) = path_buf.components().next() {
+            if is_windows_drive_segment(first) {
                 return Err(RustAnnError::py_err(
-                    "InvalidPath", 
-                    "Parent directory not in allowed locations"
+                    "InvalidPath",
+                    "Absolute paths are not allowed",
                 ));
             }
         }
-        
-        let resolved_parent = if parent.exists() {
-            parent.canonicalize()
-                .map_err(|e| RustAnnError::py_err("InvalidPath", format!("Cannot resolve parent: {}", e)))?
-        } else {
-            parent.to_path_buf()
-        };
-        
-        let filename = full_path.file_name()
-            .ok_or_else(|| RustAnnError::py_err("InvalidPath", "Invalid filename"))?;
-        
-        (resolved_parent, Some(filename))
-    };
-
-    // Check if resolved path is within allowed directories
-    let resolved_str = resolved_path.to_string_lossy();
-    if !is_path_in_allowed_dirs(&resolved_str) {
-        return Err(RustAnnError::py_err(
-            "InvalidPath", 
-            "Path is outside allowed directories"
-        ));
+        let current_dir = std::env::current_dir()
+            .map_err(|e| RustAnnError::py_err("IOError", format!("Cannot get current directory: {}", e)))?;
+        lexically_resolve(&current_dir, path_buf)
     }
+}
 
-    // Return the final safe path
-    if let Some(filename) = filename {
-        Ok(resolved_path.join(filename))
+/// Byte-native equivalent of [`expand_path`], for a path built from raw,
+/// possibly non-UTF-8 bytes via [`OsStr::from_bytes`]. Separators aren't
+/// normalized here (unlike `expand_path`'s `\`-handling for Windows-style
+/// input) since this entry point is Unix-only raw path bytes, where `\` is
+/// just an ordinary filename character.
+#[cfg(unix)]
+fn expand_path_os(path: &OsStr) -> PyResult<PathBuf> {
+    let bytes = path.as_bytes();
+    if let Some(rest) = bytes.strip_prefix(b"~") {
+        let home = home_dir()?;
+        let rest = rest.strip_prefix(b"/").unwrap_or(rest);
+        lexically_resolve(&home, Path::new(OsStr::from_bytes(rest)))
     } else {
-        Ok(resolved_path)
+        let path_buf = PathBuf::from(path);
+        if path_buf.is_absolute() {
+            return Err(RustAnnError::py_err(
+                "InvalidPath",
+                "Absolute paths are not allowed",
+            ));
+        }
+        let current_dir = std::env::current_dir()
+            .map_err(|e| RustAnnError::py_err("IOError", format!("Cannot get current directory: {}", e)))?;
+        lexically_resolve(&current_dir, &path_buf)
     }
 }
 
-/// Check if a path is within allowed base directories
-fn is_path_in_allowed_dirs(path: &str) -> bool {
+/// The current user's home directory, used to expand a leading `~`.
+fn home_dir() -> PyResult<PathBuf> {
+    std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .ok_or_else(|| RustAnnError::py_err("InvalidPath", "Cannot resolve home directory (`$HOME` is unset)"))
+}
+
+/// Resolve `rest`'s components against `anchor` without touching the
+/// filesystem: `.` is dropped, a `Normal` segment is pushed, and `..` pops
+/// the last pushed segment — but only if one has been pushed since
+/// `anchor`, so `rest` can never walk back out above it.
+fn lexically_resolve(anchor: &Path, rest: &Path) -> PyResult<PathBuf> {
+    let mut resolved = anchor.to_path_buf();
+    let mut depth_below_anchor: usize = 0;
+
+    for component in rest.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if depth_below_anchor == 0 {
+                    return Err(RustAnnError::py_err(
+                        "InvalidPath",
+                        "Path escapes above the allowed root",
+                    ));
+                }
+                resolved.pop();
+                depth_below_anchor -= 1;
+            }
+            Component::Normal(segment) => {
+                resolved.push(segment);
+                depth_below_anchor += 1;
+            }
+            Component::RootDir | Component::Prefix(_) => {
+                return Err(RustAnnError::py_err(
+                    "InvalidPath",
+                    "Absolute paths are not allowed",
+                ));
+            }
+        }
+    }
+
+    Ok(resolved)
+}
+
+/// Check if a path is within allowed base directories. Allowed directories
+/// are resolved the same lexical way as the candidate path, so this doesn't
+/// depend on them already existing on disk. Compares `Path`s directly
+/// rather than through a lossy string conversion, so this works correctly
+/// for non-UTF-8 paths too.
+fn is_path_in_allowed_dirs(path: &Path) -> bool {
     let current_dir = match std::env::current_dir() {
         Ok(dir) => dir,
         Err(_) => return false,
     };
-    
+
     for &allowed_dir in ALLOWED_BASE_DIRS {
-        let allowed_path = current_dir.join(allowed_dir);
-        
-        // Canonicalize allowed directory if it exists
-        let canonical_allowed = if allowed_path.exists() {
-            match allowed_path.canonicalize() {
-                Ok(p) => p,
-                Err(_) => continue,
-            }
-        } else {
-            allowed_path
+        let Ok(allowed_path) = lexically_resolve(&current_dir, Path::new(allowed_dir)) else {
+            continue;
         };
-        
-        let allowed_str = canonical_allowed.to_string_lossy();
-        
-        // Check if path starts with this allowed directory
-        if path.starts_with(&*allowed_str) {
+
+        if path.starts_with(&allowed_path) {
             return true;
         }
     }
-    
+
     false
 }
 
@@ -299,8 +703,118 @@ mod tests {
     fn test_is_path_in_allowed_dirs() {
         let current_dir = std::env::current_dir().unwrap();
         let _allowed_path = current_dir.join("data").to_string_lossy().to_string();
-        
-        assert!(is_path_in_allowed_dirs(&current_dir.to_string_lossy()));
+
+        assert!(is_path_in_allowed_dirs(&current_dir));
         // Other tests depend on file system state
     }
+
+    #[test]
+    fn validate_path_within_accepts_paths_inside_base() {
+        let base = std::env::temp_dir().join("rust_annie_path_validation_test_accept");
+        fs::create_dir_all(&base).unwrap();
+
+        let result = validate_path_within(&base, "nested/model.bin");
+        assert!(result.is_ok());
+        assert!(result.unwrap().starts_with(fs::canonicalize(&base).unwrap()));
+    }
+
+    #[test]
+    fn validate_path_within_rejects_escaping_parent() {
+        let base = std::env::temp_dir().join("rust_annie_path_validation_test_base");
+        fs::create_dir_all(&base).unwrap();
+
+        // `..` must be rejected even though the base itself canonicalizes fine.
+        let result = validate_path_within(&base, "../../etc/passwd");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn validate_path_within_rejects_percent_encoded_traversal() {
+        let base = std::env::temp_dir().join("rust_annie_path_validation_test_percent");
+        fs::create_dir_all(&base).unwrap();
+
+        let result = validate_path_within(&base, "%2e%2e/%2e%2e/etc/passwd");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn validate_path_within_rejects_reserved_windows_names() {
+        let base = std::env::temp_dir().join("rust_annie_path_validation_test_reserved");
+        fs::create_dir_all(&base).unwrap();
+
+        let result = validate_path_within(&base, "CON");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn validate_path_within_rejects_symlinked_parent_escaping_base() {
+        use std::os::unix::fs::symlink;
+
+        let root = std::env::temp_dir().join("rust_annie_path_validation_test_symlink_root");
+        fs::create_dir_all(&root).unwrap();
+        let base = root.join("base");
+        fs::create_dir_all(&base).unwrap();
+        let outside = root.join("outside");
+        fs::create_dir_all(&outside).unwrap();
+
+        let escape_link = base.join("escape");
+        let _ = fs::remove_file(&escape_link);
+        symlink(&outside, &escape_link).unwrap();
+
+        // `escape` canonicalizes to `outside`, a sibling of `base` rather than
+        // a descendant — the starts_with(canonical_base) check must reject it.
+        let result = validate_path_within(&base, "escape/payload.bin");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn sandbox_root_round_trips_through_set_and_get() {
+        let dir = std::env::temp_dir().join("rust_annie_path_validation_test_sandbox_root");
+        fs::create_dir_all(&dir).unwrap();
+
+        set_sandbox_root(&dir).unwrap();
+        assert_eq!(sandbox_root(), fs::canonicalize(&dir).unwrap());
+    }
+
+    #[test]
+    fn validated_path_try_from_str_rejects_traversal_and_absolute() {
+        assert!(ValidatedPath::try_from("../../../etc/passwd").is_err());
+        assert!(ValidatedPath::try_from("/etc/passwd").is_err());
+    }
+
+    #[test]
+    fn validated_path_try_from_str_accepts_plain_filename() {
+        let validated = ValidatedPath::try_from("some_model.bin").unwrap();
+        assert!(validated.as_path().is_absolute());
+    }
+
+    #[test]
+    fn validated_path_try_from_path_matches_try_from_str() {
+        let from_str = ValidatedPath::try_from("some_model.bin").unwrap();
+        let from_path = ValidatedPath::try_from(Path::new("some_model.bin")).unwrap();
+        assert_eq!(from_str, from_path);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn validated_path_try_from_bytes_matches_try_from_str() {
+        let from_str = ValidatedPath::try_from("some_model.bin").unwrap();
+        let from_bytes = ValidatedPath::try_from(b"some_model.bin".as_slice()).unwrap();
+        assert_eq!(from_str, from_bytes);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn validated_path_try_from_bytes_rejects_null_bytes() {
+        assert!(ValidatedPath::try_from(b"model\0.bin".as_slice()).is_err());
+    }
+
+    #[test]
+    fn anchored_reports_the_allowed_base_dir_it_resolved_under() {
+        let validated = ValidatedPath::try_from("model.bin").unwrap();
+        let anchored = validated.anchored().expect("'.' is always in ALLOWED_BASE_DIRS");
+        assert_eq!(anchored.base(), ".");
+        assert_eq!(anchored.relative(), Path::new("model.bin"));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,454 @@
+//! Binary (de)serialization for [`AnnIndex`], read and written
+//! incrementally so a truncated or corrupt file surfaces a [`RustAnnError`]
+//! instead of panicking partway through a single giant deserialize.
+//!
+//! Layout (all integers little-endian):
+//!
+//! ```text
+//! magic:          8 bytes, b"ANNIDX01"
+//! format_version: u32
+//! dim:            u64
+//! metric:         tagged union (see `write_metric`/`read_metric`)
+//! minkowski_p:    u8 presence flag + f32 if present
+//! body_len:       u64 — byte length of `body`
+//! body_crc32:     u32 — IEEE CRC32 of `body`
+//! body:           body_len bytes, containing:
+//!   entry_count:    u64
+//!   entries:        `entry_count` tombstone-tagged records (see `write_entry`/`read_entry`)
+//!   metadata:       optional schema + columns (see `write_metadata`/`read_metadata`)
+//!   deleted_count:      u64
+//!   max_deleted_ratio:  f32
+//! ```
+//!
+//! `body` is read with an exact-length read before anything in it is
+//! parsed, and its checksum is verified before its fields are trusted — a
+//! truncated or bit-flipped file surfaces [`RustAnnError::UnexpectedEof`] or
+//! [`RustAnnError::Corrupt`] instead of a partially-populated [`AnnIndex`].
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Cursor, Read, Write};
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::AtomicU64;
+
+use crate::errors::RustAnnError;
+use crate::index::{AnnIndex, MetadataType, MetadataValue};
+use crate::metrics::Distance;
+
+const MAGIC: &[u8; 8] = b"ANNIDX01";
+const FORMAT_VERSION: u32 = 2;
+
+/// Save `index` to `path` in this module's streaming binary format.
+pub fn save_index(index: &AnnIndex, path: &str) -> Result<(), RustAnnError> {
+    let file = File::create(path).map_err(|e| RustAnnError::io_err_with_source(e.to_string(), e))?;
+    let mut w = BufWriter::new(file);
+
+    w.write_all(MAGIC).map_err(io_err)?;
+    w.write_all(&FORMAT_VERSION.to_le_bytes()).map_err(io_err)?;
+    w.write_all(&(index.dim as u64).to_le_bytes()).map_err(io_err)?;
+    write_metric(&mut w, &index.metric)?;
+    match index.minkowski_p {
+        Some(p) => {
+            w.write_all(&[1u8]).map_err(io_err)?;
+            w.write_all(&p.to_le_bytes()).map_err(io_err)?;
+        }
+        None => w.write_all(&[0u8]).map_err(io_err)?,
+    }
+
+    let mut body = Cursor::new(Vec::new());
+    body.write_all(&(index.entries.len() as u64).to_le_bytes()).map_err(io_err)?;
+    for entry in &index.entries {
+        write_entry(&mut body, entry)?;
+    }
+    write_metadata(&mut body, &index.metadata_schema, &index.metadata_columns)?;
+    body.write_all(&(index.deleted_count as u64).to_le_bytes()).map_err(io_err)?;
+    body.write_all(&index.max_deleted_ratio.to_le_bytes()).map_err(io_err)?;
+    let body = body.into_inner();
+
+    w.write_all(&(body.len() as u64).to_le_bytes()).map_err(io_err)?;
+    w.write_all(&crc32(&body).to_le_bytes()).map_err(io_err)?;
+    w.write_all(&body).map_err(io_err)?;
+
+    w.flush().map_err(io_err)?;
+    Ok(())
+}
+
+/// Load an index from `path`, validating the header, then reading the body
+/// as one length-prefixed, checksummed block before parsing its entries —
+/// so a truncated file is rejected with [`RustAnnError::UnexpectedEof`] and
+/// a corrupted one with [`RustAnnError::Corrupt`] before any entry is
+/// trusted, rather than partway through deserializing them.
+pub fn load_index(path: &str) -> Result<AnnIndex, RustAnnError> {
+    let file = File::open(path).map_err(|e| RustAnnError::io_err_with_source(e.to_string(), e))?;
+    let mut r = BufReader::new(file);
+
+    let mut magic = [0u8; 8];
+    r.read_exact(&mut magic).map_err(io_err)?;
+    if &magic != MAGIC {
+        return Err(RustAnnError::Corrupt("not an Annie index file (bad magic)".to_string()));
+    }
+
+    let format_version = read_u32(&mut r)?;
+    if format_version != FORMAT_VERSION {
+        return Err(RustAnnError::Io(
+            format!(
+                "unsupported index format version {} (expected {})",
+                format_version, FORMAT_VERSION
+            ),
+            None,
+        ));
+    }
+
+    let dim = read_u64(&mut r)? as usize;
+    let metric = read_metric(&mut r)?;
+
+    let mut has_minkowski_p = [0u8; 1];
+    r.read_exact(&mut has_minkowski_p).map_err(io_err)?;
+    let minkowski_p = if has_minkowski_p[0] != 0 {
+        Some(read_f32(&mut r)?)
+    } else {
+        None
+    };
+
+    let body_len = read_u64(&mut r)? as usize;
+    let body_crc32 = read_u32(&mut r)?;
+    let mut body = vec![0u8; body_len];
+    r.read_exact(&mut body).map_err(io_err)?;
+    if crc32(&body) != body_crc32 {
+        return Err(RustAnnError::Corrupt("body failed CRC32 verification".to_string()));
+    }
+    let mut body = Cursor::new(body);
+
+    let entry_count = read_u64(&mut body)?;
+    let mut entries = Vec::with_capacity(entry_count as usize);
+    for _ in 0..entry_count {
+        entries.push(read_entry(&mut body, dim)?);
+    }
+
+    let (metadata_schema, metadata_columns) = read_metadata(&mut body)?;
+
+    let deleted_count = read_u64(&mut body)? as usize;
+    let max_deleted_ratio = read_f32(&mut body)?;
+
+    Ok(AnnIndex {
+        dim,
+        metric,
+        minkowski_p,
+        entries,
+        deleted_count,
+        max_deleted_ratio,
+        metrics: None,
+        boolean_filters: Mutex::new(HashMap::new()),
+        version: Arc::new(AtomicU64::new(0)),
+        metadata_schema,
+        metadata_columns,
+    })
+}
+
+fn write_entry<W: Write>(w: &mut W, entry: &Option<(i64, Vec<f32>, f32)>) -> Result<(), RustAnnError> {
+    match entry {
+        None => w.write_all(&[0u8]).map_err(io_err),
+        Some((id, vector, sq_norm)) => {
+            w.write_all(&[1u8]).map_err(io_err)?;
+            w.write_all(&id.to_le_bytes()).map_err(io_err)?;
+            for x in vector {
+                w.write_all(&x.to_le_bytes()).map_err(io_err)?;
+            }
+            w.write_all(&sq_norm.to_le_bytes()).map_err(io_err)
+        }
+    }
+}
+
+/// Read one entry slot, validating that its stored squared norm still
+/// matches its vector.
+fn read_entry<R: Read>(r: &mut R, dim: usize) -> Result<Option<(i64, Vec<f32>, f32)>, RustAnnError> {
+    let mut tag = [0u8; 1];
+    r.read_exact(&mut tag).map_err(io_err)?;
+    if tag[0] == 0 {
+        return Ok(None);
+    }
+
+    let id = read_i64(r)?;
+    let mut vector = Vec::with_capacity(dim);
+    for _ in 0..dim {
+        vector.push(read_f32(r)?);
+    }
+    let sq_norm = read_f32(r)?;
+
+    // Recomputes the squared norm the way `AnnIndex` stores it (sum of
+    // squares, no square root) with a 0.001 tolerance, so an entry whose
+    // stored norm disagrees with its vector is rejected while it's still
+    // streaming in.
+    let computed_sq_norm = vector.iter().map(|x| x * x).sum::<f32>();
+    if (computed_sq_norm - sq_norm).abs() > 0.001 {
+        return Err(RustAnnError::Corrupt(format!(
+            "corrupt entry {}: stored squared norm {} but vector gives {}",
+            id, sq_norm, computed_sq_norm
+        )));
+    }
+
+    Ok(Some((id, vector, sq_norm)))
+}
+
+pub(crate) fn write_metric<W: Write>(w: &mut W, metric: &Distance) -> Result<(), RustAnnError> {
+    let (tag, custom_name) = match metric {
+        Distance::Euclidean() => (0u8, None),
+        Distance::Cosine() => (1u8, None),
+        Distance::Manhattan() => (2u8, None),
+        Distance::Chebyshev() => (3u8, None),
+        Distance::Minkowski(p) => {
+            w.write_all(&[4u8]).map_err(io_err)?;
+            w.write_all(&p.to_le_bytes()).map_err(io_err)?;
+            return Ok(());
+        }
+        Distance::Hamming() => (5u8, None),
+        Distance::Jaccard() => (6u8, None),
+        Distance::Angular() => (7u8, None),
+        Distance::Canberra() => (8u8, None),
+        Distance::Custom(name) => (9u8, Some(name.clone())),
+    };
+    w.write_all(&[tag]).map_err(io_err)?;
+    if let Some(name) = custom_name {
+        write_string(w, &name)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn read_metric<R: Read>(r: &mut R) -> Result<Distance, RustAnnError> {
+    let mut tag = [0u8; 1];
+    r.read_exact(&mut tag).map_err(io_err)?;
+    Ok(match tag[0] {
+        0 => Distance::Euclidean(),
+        1 => Distance::Cosine(),
+        2 => Distance::Manhattan(),
+        3 => Distance::Chebyshev(),
+        4 => Distance::Minkowski(read_f32(r)?),
+        5 => Distance::Hamming(),
+        6 => Distance::Jaccard(),
+        7 => Distance::Angular(),
+        8 => Distance::Canberra(),
+        9 => Distance::Custom(read_string(r)?),
+        other => {
+            return Err(RustAnnError::Io(format!("unknown metric tag {}", other), None));
+        }
+    })
+}
+
+fn write_metadata<W: Write>(
+    w: &mut W,
+    schema: &Option<HashMap<String, MetadataType>>,
+    columns: &Option<HashMap<String, Vec<MetadataValue>>>,
+) -> Result<(), RustAnnError> {
+    match schema {
+        None => w.write_all(&[0u8]).map_err(io_err)?,
+        Some(schema) => {
+            w.write_all(&[1u8]).map_err(io_err)?;
+            w.write_all(&(schema.len() as u32).to_le_bytes()).map_err(io_err)?;
+            for (name, field_type) in schema {
+                write_string(w, name)?;
+                w.write_all(&[metadata_type_tag(field_type)]).map_err(io_err)?;
+            }
+        }
+    }
+
+    match columns {
+        None => w.write_all(&[0u8]).map_err(io_err),
+        Some(columns) => {
+            w.write_all(&[1u8]).map_err(io_err)?;
+            w.write_all(&(columns.len() as u32).to_le_bytes()).map_err(io_err)?;
+            for (name, values) in columns {
+                write_string(w, name)?;
+                w.write_all(&(values.len() as u32).to_le_bytes()).map_err(io_err)?;
+                for value in values {
+                    write_metadata_value(w, value)?;
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+fn read_metadata<R: Read>(
+    r: &mut R,
+) -> Result<(Option<HashMap<String, MetadataType>>, Option<HashMap<String, Vec<MetadataValue>>>), RustAnnError> {
+    let mut has_schema = [0u8; 1];
+    r.read_exact(&mut has_schema).map_err(io_err)?;
+    let schema = if has_schema[0] != 0 {
+        let field_count = read_u32(r)?;
+        let mut map = HashMap::with_capacity(field_count as usize);
+        for _ in 0..field_count {
+            let name = read_string(r)?;
+            let field_type = read_metadata_type(r)?;
+            map.insert(name, field_type);
+        }
+        Some(map)
+    } else {
+        None
+    };
+
+    let mut has_columns = [0u8; 1];
+    r.read_exact(&mut has_columns).map_err(io_err)?;
+    let columns = if has_columns[0] != 0 {
+        let column_count = read_u32(r)?;
+        let mut map = HashMap::with_capacity(column_count as usize);
+        for _ in 0..column_count {
+            let name = read_string(r)?;
+            let value_count = read_u32(r)?;
+            let mut values = Vec::with_capacity(value_count as usize);
+            for _ in 0..value_count {
+                values.push(read_metadata_value(r)?);
+            }
+            map.insert(name, values);
+        }
+        Some(map)
+    } else {
+        None
+    };
+
+    Ok((schema, columns))
+}
+
+fn metadata_type_tag(field_type: &MetadataType) -> u8 {
+    match field_type {
+        MetadataType::Int => 0,
+        MetadataType::Float => 1,
+        MetadataType::String => 2,
+        MetadataType::Tags => 3,
+        MetadataType::Timestamp => 4,
+    }
+}
+
+fn read_metadata_type<R: Read>(r: &mut R) -> Result<MetadataType, RustAnnError> {
+    let mut tag = [0u8; 1];
+    r.read_exact(&mut tag).map_err(io_err)?;
+    Ok(match tag[0] {
+        0 => MetadataType::Int,
+        1 => MetadataType::Float,
+        2 => MetadataType::String,
+        3 => MetadataType::Tags,
+        4 => MetadataType::Timestamp,
+        other => return Err(RustAnnError::Io(format!("unknown metadata type tag {}", other), None)),
+    })
+}
+
+fn write_metadata_value<W: Write>(w: &mut W, value: &MetadataValue) -> Result<(), RustAnnError> {
+    match value {
+        MetadataValue::Int(v) => {
+            w.write_all(&[0u8]).map_err(io_err)?;
+            w.write_all(&v.to_le_bytes()).map_err(io_err)
+        }
+        MetadataValue::Float(v) => {
+            w.write_all(&[1u8]).map_err(io_err)?;
+            w.write_all(&v.to_le_bytes()).map_err(io_err)
+        }
+        MetadataValue::String(v) => {
+            w.write_all(&[2u8]).map_err(io_err)?;
+            write_string(w, v)
+        }
+        MetadataValue::Tags(tags) => {
+            w.write_all(&[3u8]).map_err(io_err)?;
+            w.write_all(&(tags.len() as u32).to_le_bytes()).map_err(io_err)?;
+            for tag in tags {
+                write_string(w, tag)?;
+            }
+            Ok(())
+        }
+        MetadataValue::Timestamp(v) => {
+            w.write_all(&[4u8]).map_err(io_err)?;
+            w.write_all(&v.to_le_bytes()).map_err(io_err)
+        }
+    }
+}
+
+fn read_metadata_value<R: Read>(r: &mut R) -> Result<MetadataValue, RustAnnError> {
+    let mut tag = [0u8; 1];
+    r.read_exact(&mut tag).map_err(io_err)?;
+    Ok(match tag[0] {
+        0 => MetadataValue::Int(read_i64(r)?),
+        1 => MetadataValue::Float(read_f64(r)?),
+        2 => MetadataValue::String(read_string(r)?),
+        3 => {
+            let count = read_u32(r)?;
+            let mut tags = Vec::with_capacity(count as usize);
+            for _ in 0..count {
+                tags.push(read_string(r)?);
+            }
+            MetadataValue::Tags(tags)
+        }
+        4 => MetadataValue::Timestamp(read_i64(r)?),
+        other => return Err(RustAnnError::Io(format!("unknown metadata value tag {}", other), None)),
+    })
+}
+
+fn write_string<W: Write>(w: &mut W, s: &str) -> Result<(), RustAnnError> {
+    let bytes = s.as_bytes();
+    w.write_all(&(bytes.len() as u32).to_le_bytes()).map_err(io_err)?;
+    w.write_all(bytes).map_err(io_err)
+}
+
+fn read_string<R: Read>(r: &mut R) -> Result<String, RustAnnError> {
+    let len = read_u32(r)? as usize;
+    let mut buf = vec![0u8; len];
+    r.read_exact(&mut buf).map_err(io_err)?;
+    String::from_utf8(buf).map_err(|e| RustAnnError::io_err_with_source(format!("invalid UTF-8 string: {e}"), e))
+}
+
+pub(crate) fn read_u32<R: Read>(r: &mut R) -> Result<u32, RustAnnError> {
+    let mut buf = [0u8; 4];
+    r.read_exact(&mut buf).map_err(io_err)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+pub(crate) fn read_u64<R: Read>(r: &mut R) -> Result<u64, RustAnnError> {
+    let mut buf = [0u8; 8];
+    r.read_exact(&mut buf).map_err(io_err)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_i64<R: Read>(r: &mut R) -> Result<i64, RustAnnError> {
+    let mut buf = [0u8; 8];
+    r.read_exact(&mut buf).map_err(io_err)?;
+    Ok(i64::from_le_bytes(buf))
+}
+
+pub(crate) fn read_f32<R: Read>(r: &mut R) -> Result<f32, RustAnnError> {
+    let mut buf = [0u8; 4];
+    r.read_exact(&mut buf).map_err(io_err)?;
+    Ok(f32::from_le_bytes(buf))
+}
+
+fn read_f64<R: Read>(r: &mut R) -> Result<f64, RustAnnError> {
+    let mut buf = [0u8; 8];
+    r.read_exact(&mut buf).map_err(io_err)?;
+    Ok(f64::from_le_bytes(buf))
+}
+
+/// Map a read/write `io::Error` to a `RustAnnError`, distinguishing a clean
+/// truncation (`ErrorKind::UnexpectedEof`, which every `read_exact` in this
+/// module hits on a short file) from any other I/O failure, so a caller can
+/// tell "the file was cut off" apart from "the disk/permissions are broken"
+/// instead of both surfacing as the same opaque `Io` message.
+pub(crate) fn io_err(e: std::io::Error) -> RustAnnError {
+    let msg = e.to_string();
+    if e.kind() == std::io::ErrorKind::UnexpectedEof {
+        RustAnnError::UnexpectedEof(msg, Some(Box::new(e)))
+    } else {
+        RustAnnError::io_err_with_source(msg, e)
+    }
+}
+
+/// IEEE CRC-32 (the polynomial used by zip/gzip/tar) of `data`, used to
+/// detect a corrupted or truncated-then-patched body section before any of
+/// its entries are trusted.
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0xFFFF_FFFF;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}